@@ -0,0 +1,54 @@
+//! What a checker can compute, as reported by its `capabilities` query.
+
+use crate::{fractal::FractalKind, Fractal};
+use alloc::vec::Vec;
+use parity_scale_codec::{Decode, Encode};
+use scale_info::TypeInfo;
+
+/// Version of the messages between the manager and the checkers. It changes
+/// whenever `CheckMandelbrotPoints` or `ResultCalculated` change.
+pub const PROTOCOL_VERSION: u32 = 2;
+
+#[derive(Encode, Decode, TypeInfo, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Arithmetic {
+    /// Q4.59 numbers, see `crate::kernel`.
+    FixedPoint,
+    /// Deep zooms around a reference orbit, see `crate::perturbation`.
+    Perturbation,
+}
+
+#[derive(Encode, Decode, TypeInfo, Clone, Debug, PartialEq, Eq)]
+pub struct Capabilities {
+    /// `PROTOCOL_VERSION` of the checker.
+    pub version: u32,
+    pub fractals: Vec<FractalKind>,
+    pub arithmetic: Vec<Arithmetic>,
+    /// Largest number of points the checker computes in one message.
+    pub max_batch_size: u32,
+    /// Largest `max_iter` the checker accepts.
+    pub max_iter: u32,
+}
+
+impl Capabilities {
+    /// Whether the checker speaks the protocol of the manager and computes at
+    /// least the Mandelbrot set.
+    pub fn is_compatible(&self) -> bool {
+        self.version == PROTOCOL_VERSION
+            && self.fractals.contains(&FractalKind::Mandelbrot)
+            && self.arithmetic.contains(&Arithmetic::FixedPoint)
+            && self.max_batch_size > 0
+            && self.max_iter > 0
+    }
+
+    /// Whether the checker can compute the points of a job.
+    pub fn supports(&self, fractal: &Fractal, deep_zoom: bool, max_iter: u32) -> bool {
+        let arithmetic = if deep_zoom {
+            Arithmetic::Perturbation
+        } else {
+            Arithmetic::FixedPoint
+        };
+        self.fractals.contains(&fractal.kind())
+            && self.arithmetic.contains(&arithmetic)
+            && max_iter <= self.max_iter
+    }
+}
@@ -0,0 +1,74 @@
+use alloc::vec::Vec;
+use num_bigint::{BigInt, BigUint};
+use parity_scale_codec::{Decode, Encode};
+use rust_decimal::Decimal;
+use scale_info::TypeInfo;
+
+/// Decimal number `num * 10^-scale`, the wire format of coordinates.
+#[derive(Encode, Decode, TypeInfo, Clone, Debug, PartialEq, Eq)]
+pub struct FixedPoint {
+    pub num: i64,
+    pub scale: u32,
+}
+
+impl FixedPoint {
+    /// Converts a decimal, dropping trailing zeros and rounding away the least
+    /// significant digits when the mantissa does not fit into `i64`. Integers
+    /// beyond the range of `i64` saturate.
+    pub fn from_decimal(decimal: Decimal) -> Self {
+        let mut decimal = decimal.normalize();
+        while i64::try_from(decimal.mantissa()).is_err() {
+            if decimal.scale() == 0 {
+                let num = if decimal.is_sign_negative() {
+                    i64::MIN
+                } else {
+                    i64::MAX
+                };
+                return Self { num, scale: 0 };
+            }
+            decimal = decimal.round_dp(decimal.scale() - 1).normalize();
+        }
+        Self {
+            num: decimal.mantissa() as i64,
+            scale: decimal.scale(),
+        }
+    }
+
+    pub fn to_decimal(&self) -> Decimal {
+        Decimal::new(self.num, self.scale)
+    }
+
+    /// Like `to_decimal`, but returns `None` when the scale is above the 28 digits
+    /// a decimal supports.
+    pub fn try_to_decimal(&self) -> Option<Decimal> {
+        Decimal::try_new(self.num, self.scale).ok()
+    }
+}
+
+/// Decimal number `num * 10^-scale` of any precision, for coordinates that do
+/// not fit into a `FixedPoint`. `num` is a signed integer in little-endian two's
+/// complement bytes.
+#[derive(Encode, Decode, TypeInfo, Clone, Debug, PartialEq, Eq)]
+pub struct BigFixedPoint {
+    pub num: Vec<u8>,
+    pub scale: u32,
+}
+
+impl BigFixedPoint {
+    pub fn new(num: &BigInt, scale: u32) -> Self {
+        Self {
+            num: num.to_signed_bytes_le(),
+            scale,
+        }
+    }
+
+    pub fn to_bigint(&self) -> BigInt {
+        BigInt::from_signed_bytes_le(&self.num)
+    }
+
+    /// Whether the magnitude of the number is at most `bound`.
+    pub fn is_within(&self, bound: u32) -> bool {
+        self.to_bigint().magnitude()
+            <= &(BigUint::from(bound) * BigUint::from(10u32).pow(self.scale))
+    }
+}
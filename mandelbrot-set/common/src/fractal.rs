@@ -0,0 +1,61 @@
+use crate::FixedPoint;
+use parity_scale_codec::{Decode, Encode};
+use rust_decimal::Decimal;
+use scale_info::TypeInfo;
+
+/// Highest power of `z` a multibrot job can use.
+pub const MAX_MULTIBROT_POWER: u32 = 8;
+
+/// The iterated map of a render job. The point of the image is called `c`;
+/// every map is iterated until `|z| > 2`, see `crate::kernel`.
+#[derive(Encode, Decode, TypeInfo, Clone, Debug, PartialEq, Eq)]
+pub enum Fractal {
+    /// `z = z^2 + c`, starting from `z = c`.
+    Mandelbrot,
+    /// `z = z^2 + k` for a fixed `k = c_re + c_im * i`, starting from `z = c`.
+    Julia { c_re: FixedPoint, c_im: FixedPoint },
+    /// `z = z^power + c`, starting from `z = c`.
+    Multibrot { power: u32 },
+    /// `z = (|Re z| + |Im z| * i)^2 + c`, starting from `z = c`. The imaginary
+    /// axis points up, so the ship is upside down compared to most pictures.
+    BurningShip,
+}
+
+/// The family of a `Fractal`, without its parameters.
+#[derive(Encode, Decode, TypeInfo, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FractalKind {
+    Mandelbrot,
+    Julia,
+    Multibrot,
+    BurningShip,
+}
+
+impl Fractal {
+    pub fn kind(&self) -> FractalKind {
+        match self {
+            Fractal::Mandelbrot => FractalKind::Mandelbrot,
+            Fractal::Julia { .. } => FractalKind::Julia,
+            Fractal::Multibrot { .. } => FractalKind::Multibrot,
+            Fractal::BurningShip => FractalKind::BurningShip,
+        }
+    }
+
+    /// Multibrot powers must be between 2 and `MAX_MULTIBROT_POWER`, and the
+    /// Julia parameter at most 2 in magnitude, so that the escape radius of 2
+    /// holds for every map.
+    pub fn is_valid(&self) -> bool {
+        match self {
+            Fractal::Mandelbrot | Fractal::BurningShip => true,
+            Fractal::Julia { c_re, c_im } => {
+                let (Some(c_re), Some(c_im)) = (c_re.try_to_decimal(), c_im.try_to_decimal())
+                else {
+                    return false;
+                };
+                c_re.abs() <= Decimal::TWO
+                    && c_im.abs() <= Decimal::TWO
+                    && c_re * c_re + c_im * c_im <= Decimal::from(4)
+            }
+            Fractal::Multibrot { power } => (2..=MAX_MULTIBROT_POWER).contains(power),
+        }
+    }
+}
@@ -0,0 +1,74 @@
+//! Row-major pixel layout of a rendered image.
+//!
+//! Pixel `index` lies in column `index % width` and row `index / width`. Column 0
+//! is the left edge of the image (real part `x_min`) and row 0 is its top edge
+//! (imaginary part `y_max`). The pixel at `(column, row)` samples
+//! `x_min + column * (x_max - x_min) / width` on the real axis and
+//! `y_max - row * (y_max - y_min) / height` on the imaginary one.
+
+use crate::FixedPoint;
+use rust_decimal::Decimal;
+
+/// Column and row of the pixel with the given index.
+pub fn pixel_position(index: u32, width: u32) -> (u32, u32) {
+    (index % width, index / width)
+}
+
+/// Index of the pixel at the given column and row.
+pub fn pixel_index(column: u32, row: u32, width: u32) -> u32 {
+    row * width + column
+}
+
+/// Maps pixel indexes of an image to points of the complex plane.
+#[derive(Clone, Debug)]
+pub struct Viewport {
+    pub width: u32,
+    pub height: u32,
+    x_min: Decimal,
+    y_max: Decimal,
+    step_x: Decimal,
+    step_y: Decimal,
+}
+
+impl Viewport {
+    pub fn new(
+        width: u32,
+        height: u32,
+        x_min: &FixedPoint,
+        x_max: &FixedPoint,
+        y_min: &FixedPoint,
+        y_max: &FixedPoint,
+    ) -> Self {
+        let x_min = x_min.to_decimal();
+        let y_max = y_max.to_decimal();
+        Self {
+            width,
+            height,
+            x_min,
+            y_max,
+            step_x: (x_max.to_decimal() - x_min) / Decimal::from(width),
+            step_y: (y_max - y_min.to_decimal()) / Decimal::from(height),
+        }
+    }
+
+    pub fn total_points(&self) -> u32 {
+        self.width * self.height
+    }
+
+    /// Real and imaginary parts of the point sampled by the pixel.
+    pub fn decimal_coordinates(&self, index: u32) -> (Decimal, Decimal) {
+        let (column, row) = pixel_position(index, self.width);
+        (
+            self.x_min + Decimal::from(column) * self.step_x,
+            self.y_max - Decimal::from(row) * self.step_y,
+        )
+    }
+
+    pub fn coordinates(&self, index: u32) -> (FixedPoint, FixedPoint) {
+        let (c_re, c_im) = self.decimal_coordinates(index);
+        (
+            FixedPoint::from_decimal(c_re),
+            FixedPoint::from_decimal(c_im),
+        )
+    }
+}
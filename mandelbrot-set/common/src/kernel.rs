@@ -0,0 +1,351 @@
+//! Escape-time iteration of the maps of `Fractal`.
+//!
+//! The result is the first iteration at which `|z|^2 > 4`, or `max_iter` when the
+//! orbit stays bounded. `escape_time` runs on binary fixed-point numbers in the
+//! Q4.59 format: an `i64` holding the value times `2^59`, so that every number
+//! with a magnitude below 16 fits. Products are taken in `i128` and shifted
+//! back. `escape_time_decimal` is the reference implementation of the
+//! Mandelbrot set on `rust_decimal::Decimal` it is verified against.
+//!
+//! Points that never escape would run all `max_iter` iterations, so
+//! `escape_time` answers `max_iter` early for them when it can:
+//! - points of the main cardioid and of the period-2 bulb of the Mandelbrot set
+//!   are recognized by their closed-form equations before iterating;
+//! - other bounded orbits end up repeating exactly, as the fixed-point numbers
+//!   take finitely many values. Brent's cycle detection compares `z` with a
+//!   value saved at the start of windows of doubling length and stops at the
+//!   first repetition. An orbit that repeats can never escape, so this does not
+//!   change the result.
+//!
+//! `smooth_escape_time` also returns a fractional iteration count for smooth
+//! coloring. Once the orbit has escaped, it keeps iterating with 32 fractional
+//! bits until `|z|^power > 2^16` (`|z| > 256` for the quadratic maps) and then
+//! computes `n + 1 - log_power(ln |z|)`, which, unlike `n`, is continuous
+//! across the plane.
+
+use crate::{FixedPoint, Fractal};
+use rust_decimal::Decimal;
+
+pub(crate) const FRAC_BITS: u32 = 59;
+const ONE: i128 = 1 << FRAC_BITS;
+/// Escape radius squared.
+pub(crate) const BAILOUT: i128 = 4 * ONE;
+/// Largest magnitude of a coordinate whose orbit does not escape at once.
+pub(crate) const MAX_COORDINATE: i128 = 2 * ONE;
+const QUARTER: i128 = ONE / 4;
+const SIXTEENTH: i128 = ONE / 16;
+
+/// Fractional bits of the numbers used after the escape.
+const SMOOTH_FRAC_BITS: u32 = 32;
+/// Binary logarithm of `|z|^power` at the escape radius of the smooth iteration count.
+const SMOOTH_BAILOUT_LOG2: u32 = 16;
+/// Bounds the iterations between the two radii, which take a few steps at most.
+const MAX_SMOOTH_STEPS: u32 = 64;
+/// `-log2(ln 2)` in Q32.
+const NEG_LOG2_LN2: i128 = 2_271_034_279;
+/// Decimal digits of the smooth iteration counts.
+pub const SMOOTH_SCALE: u32 = 9;
+
+/// Converts a decimal coordinate to Q4.59, rounding to the nearest
+/// representable value. Returns `None` when its magnitude is above 2: such
+/// points escape before the first iteration.
+pub(crate) fn to_q59(value: &FixedPoint) -> Option<i64> {
+    let num = (value.num as i128) << FRAC_BITS;
+    let q = match 10i128.checked_pow(value.scale) {
+        Some(divisor) => {
+            let half = divisor / 2;
+            if num < 0 {
+                (num - half) / divisor
+            } else {
+                (num + half) / divisor
+            }
+        }
+        // Below 10^-20, which rounds to zero in Q4.59.
+        None => 0,
+    };
+    (q.abs() <= MAX_COORDINATE).then_some(q as i64)
+}
+
+fn mul(a: i64, b: i64) -> i128 {
+    (a as i128 * b as i128) >> FRAC_BITS
+}
+
+/// Whether the point lies in the main cardioid or in the period-2 bulb.
+fn in_main_components(c_re: i64, c_im: i64) -> bool {
+    let im_squared = mul(c_im, c_im);
+
+    // Cardioid: q * (q + (x - 1/4)) <= y^2 / 4, where q = (x - 1/4)^2 + y^2.
+    let shifted = (c_re as i128 - QUARTER) as i64;
+    let q = (mul(shifted, shifted) + im_squared) as i64;
+    if mul(q, (q as i128 + shifted as i128) as i64) <= im_squared / 4 {
+        return true;
+    }
+
+    // Bulb: (x + 1)^2 + y^2 <= 1/16.
+    let shifted = (c_re as i128 + ONE) as i64;
+    mul(shifted, shifted) + im_squared <= SIXTEENTH
+}
+
+/// The map iterated by a fractal, `z^power` plus a constant.
+#[derive(Clone, Copy)]
+pub(crate) enum Map {
+    Square,
+    BurningShip,
+    Power(u32),
+}
+
+impl Map {
+    fn power(self) -> u32 {
+        match self {
+            Map::Square | Map::BurningShip => 2,
+            Map::Power(power) => power,
+        }
+    }
+
+    /// The map without its constant, on numbers with `frac_bits` fractional bits.
+    fn apply(self, re: i128, im: i128, frac_bits: u32) -> (i128, i128) {
+        match self {
+            Map::Square => ((re * re - im * im) >> frac_bits, (2 * re * im) >> frac_bits),
+            Map::BurningShip => (
+                (re * re - im * im) >> frac_bits,
+                (2 * re.abs() * im.abs()) >> frac_bits,
+            ),
+            Map::Power(power) => {
+                let (mut power_re, mut power_im) = (re, im);
+                for _ in 1..power {
+                    (power_re, power_im) = (
+                        (power_re * re - power_im * im) >> frac_bits,
+                        (power_re * im + power_im * re) >> frac_bits,
+                    );
+                }
+                (power_re, power_im)
+            }
+        }
+    }
+}
+
+/// Iteration at which an orbit left the disc of radius 2, and the value of `z`
+/// then, in Q32.
+pub(crate) struct Escape {
+    pub iter: u32,
+    z_re: i128,
+    z_im: i128,
+}
+
+impl Escape {
+    pub fn new(iter: u32, z_re: i128, z_im: i128) -> Self {
+        let shift = FRAC_BITS - SMOOTH_FRAC_BITS;
+        Self {
+            iter,
+            z_re: z_re >> shift,
+            z_im: z_im >> shift,
+        }
+    }
+}
+
+/// An orbit in Q4.59: its start, the map and the constant added at every step.
+struct Orbit {
+    map: Map,
+    z_re: i64,
+    z_im: i64,
+    k_re: i64,
+    k_im: i64,
+    /// Whether the closed-form tests of the Mandelbrot set apply.
+    mandelbrot: bool,
+}
+
+impl Orbit {
+    /// Returns `None` when the point, or the parameter of a Julia set, has a
+    /// coordinate above 2: such points escape before the first iteration.
+    fn new(fractal: &Fractal, c_re: &FixedPoint, c_im: &FixedPoint) -> Option<Self> {
+        let (z_re, z_im) = (to_q59(c_re)?, to_q59(c_im)?);
+        let (map, k_re, k_im) = match fractal {
+            Fractal::Mandelbrot | Fractal::Multibrot { power: 2 } => (Map::Square, z_re, z_im),
+            Fractal::Julia { c_re, c_im } => (Map::Square, to_q59(c_re)?, to_q59(c_im)?),
+            Fractal::Multibrot { power } => (Map::Power(*power), z_re, z_im),
+            Fractal::BurningShip => (Map::BurningShip, z_re, z_im),
+        };
+        Some(Self {
+            map,
+            z_re,
+            z_im,
+            k_re,
+            k_im,
+            mandelbrot: matches!(map, Map::Square) && !matches!(fractal, Fractal::Julia { .. }),
+        })
+    }
+
+    fn escape(self, max_iter: u32) -> Option<Escape> {
+        if self.mandelbrot && in_main_components(self.k_re, self.k_im) {
+            return None;
+        }
+
+        let (mut z_re, mut z_im) = (self.z_re, self.z_im);
+        let (mut saved_re, mut saved_im) = (z_re, z_im);
+        let mut window = 1u32;
+        let mut steps = 0u32;
+        for i in 0..max_iter {
+            let re_squared = mul(z_re, z_re);
+            let im_squared = mul(z_im, z_im);
+            if re_squared + im_squared > BAILOUT {
+                return Some(Escape::new(i, z_re as i128, z_im as i128));
+            }
+
+            // Both parts are at most 2 here, so the new ones stay below
+            // `2^power + 2`, which fits into `i128`.
+            let (new_re, new_im) = match self.map {
+                Map::Square => (re_squared - im_squared, 2 * mul(z_re, z_im)),
+                Map::BurningShip => (re_squared - im_squared, 2 * mul(z_re.abs(), z_im.abs())),
+                Map::Power(_) => self.map.apply(z_re as i128, z_im as i128, FRAC_BITS),
+            };
+            let (new_re, new_im) = (new_re + self.k_re as i128, new_im + self.k_im as i128);
+            // A part above 2 escapes at the next iteration and may not fit into Q4.59.
+            if new_re.abs() > MAX_COORDINATE || new_im.abs() > MAX_COORDINATE {
+                return (i + 1 < max_iter).then(|| Escape::new(i + 1, new_re, new_im));
+            }
+            (z_re, z_im) = (new_re as i64, new_im as i64);
+
+            if (z_re, z_im) == (saved_re, saved_im) {
+                return None;
+            }
+            steps += 1;
+            if steps == window {
+                (saved_re, saved_im) = (z_re, z_im);
+                steps = 0;
+                window = window.saturating_mul(2);
+            }
+        }
+
+        None
+    }
+}
+
+/// Escape iteration of the point `c_re + c_im * i`, see the module docs.
+pub fn escape_time(fractal: &Fractal, c_re: &FixedPoint, c_im: &FixedPoint, max_iter: u32) -> u32 {
+    let Some(orbit) = Orbit::new(fractal, c_re, c_im) else {
+        return 0;
+    };
+    orbit
+        .escape(max_iter)
+        .map_or(max_iter, |escape| escape.iter)
+}
+
+/// `escape_time` together with the smooth iteration count, a decimal with
+/// `SMOOTH_SCALE` digits between 0 and `max_iter`. Points that do not escape get
+/// `max_iter`, and points with a coordinate above 2 get 0.
+pub fn smooth_escape_time(
+    fractal: &Fractal,
+    c_re: &FixedPoint,
+    c_im: &FixedPoint,
+    max_iter: u32,
+) -> (u32, FixedPoint) {
+    let Some(orbit) = Orbit::new(fractal, c_re, c_im) else {
+        return (0, smooth_to_fixed_point(0));
+    };
+    let (map, k_re, k_im) = (orbit.map, orbit.k_re, orbit.k_im);
+    match orbit.escape(max_iter) {
+        Some(escape) => (
+            escape.iter,
+            smooth_iterations(map, k_re, k_im, &escape, max_iter),
+        ),
+        None => (max_iter, bounded_smooth_iterations(max_iter)),
+    }
+}
+
+/// Smooth iteration count of a point that does not escape.
+pub(crate) fn bounded_smooth_iterations(max_iter: u32) -> FixedPoint {
+    smooth_to_fixed_point((max_iter as i128) << SMOOTH_FRAC_BITS)
+}
+
+/// Smooth iteration count of an orbit of `map` plus `k_re + k_im * i`, given in
+/// Q4.59, that escaped.
+pub(crate) fn smooth_iterations(
+    map: Map,
+    k_re: i64,
+    k_im: i64,
+    escape: &Escape,
+    max_iter: u32,
+) -> FixedPoint {
+    let shift = FRAC_BITS - SMOOTH_FRAC_BITS;
+    let (k_re, k_im) = ((k_re >> shift) as i128, (k_im >> shift) as i128);
+    let power = map.power();
+    let bailout = 1u128 << (2 * SMOOTH_BAILOUT_LOG2 / power + SMOOTH_FRAC_BITS);
+    let (mut z_re, mut z_im) = (escape.z_re, escape.z_im);
+    let mut iter = escape.iter;
+    let mut modulus_squared = modulus_squared_q32(z_re, z_im);
+    for _ in 0..MAX_SMOOTH_STEPS {
+        if modulus_squared > bailout {
+            break;
+        }
+        let (new_re, new_im) = map.apply(z_re, z_im, SMOOTH_FRAC_BITS);
+        (z_re, z_im) = (new_re + k_re, new_im + k_im);
+        modulus_squared = modulus_squared_q32(z_re, z_im);
+        iter += 1;
+    }
+
+    // log2 |z| = log2 |z|^2 / 2, ln |z| = log2 |z| * ln 2 and
+    // log_power(x) = log2(x) / log2(power).
+    let log2_modulus = (log2_q32(modulus_squared) / 2).max(1) as u128;
+    let log_power_ln_modulus = ((log2_q32(log2_modulus) - NEG_LOG2_LN2) << SMOOTH_FRAC_BITS)
+        / log2_q32((power as u128) << SMOOTH_FRAC_BITS);
+    let smooth = ((iter as i128 + 1) << SMOOTH_FRAC_BITS) - log_power_ln_modulus;
+    smooth_to_fixed_point(smooth.clamp(0, (max_iter as i128) << SMOOTH_FRAC_BITS))
+}
+
+fn modulus_squared_q32(re: i128, im: i128) -> u128 {
+    ((re * re + im * im) >> SMOOTH_FRAC_BITS) as u128
+}
+
+/// Binary logarithm of a positive Q32 number, in Q32.
+fn log2_q32(value: u128) -> i128 {
+    let msb = 127 - value.leading_zeros();
+    let integer = msb as i128 - SMOOTH_FRAC_BITS as i128;
+
+    // Mantissa in [1, 2), whose square gives the next fractional bit.
+    let mut mantissa = if msb >= SMOOTH_FRAC_BITS {
+        value >> (msb - SMOOTH_FRAC_BITS)
+    } else {
+        value << (SMOOTH_FRAC_BITS - msb)
+    };
+    let mut fraction = 0i128;
+    for bit in (0..SMOOTH_FRAC_BITS).rev() {
+        mantissa = (mantissa * mantissa) >> SMOOTH_FRAC_BITS;
+        if mantissa >= 2 << SMOOTH_FRAC_BITS {
+            mantissa >>= 1;
+            fraction |= 1 << bit;
+        }
+    }
+    (integer << SMOOTH_FRAC_BITS) + fraction
+}
+
+fn smooth_to_fixed_point(value: i128) -> FixedPoint {
+    let scaled = value * 10i128.pow(SMOOTH_SCALE) + (1 << (SMOOTH_FRAC_BITS - 1));
+    FixedPoint {
+        num: (scaled >> SMOOTH_FRAC_BITS) as i64,
+        scale: SMOOTH_SCALE,
+    }
+}
+
+/// `escape_time` of the Mandelbrot set on `rust_decimal::Decimal`. Much slower,
+/// kept as the reference the fixed-point kernel is checked against.
+pub fn escape_time_decimal(c_re: Decimal, c_im: Decimal, max_iter: u32) -> u32 {
+    let mut z_re = c_re;
+    let mut z_im = c_im;
+
+    // Threshold
+    let threshold = Decimal::from(4);
+
+    for i in 0..max_iter {
+        let modulus_squared = z_re * z_re + z_im * z_im;
+        if modulus_squared > threshold {
+            return i;
+        }
+
+        // z: z = z^2 + c
+        let new_re = z_re * z_re - z_im * z_im + c_re;
+        z_im = Decimal::from(2) * z_re * z_im + c_im;
+        z_re = new_re;
+    }
+
+    max_iter
+}
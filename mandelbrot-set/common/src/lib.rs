@@ -0,0 +1,17 @@
+#![no_std]
+
+//! Types and conventions shared by the manager, the checker and the off-chain renderer.
+
+extern crate alloc;
+
+pub mod capabilities;
+mod fixed_point;
+pub mod fractal;
+pub mod grid;
+pub mod kernel;
+pub mod perturbation;
+
+pub use capabilities::Capabilities;
+pub use fixed_point::{BigFixedPoint, FixedPoint};
+pub use fractal::Fractal;
+pub use grid::Viewport;
@@ -0,0 +1,328 @@
+//! Deep-zoom rendering of the Mandelbrot set by perturbation.
+//!
+//! Past a zoom of about `1e-14`, neighbouring pixels no longer differ in a
+//! `FixedPoint` or in the Q4.59 numbers of `kernel`. Instead, the orbit of one
+//! reference point `C` is computed once with arbitrary-precision integers
+//! (`reference_orbit`), and every pixel `c = C + dc` only follows its distance
+//! `d` to that orbit: with `Z_0 = 0` and `Z_{n+1} = Z_n^2 + C`, the pixel is at
+//! `Z_n + d_n` where `d_{n+1} = 2 Z_n d_n + d_n^2 + dc`. The distances are tiny
+//! but need little precision, so they are kept in a software floating-point
+//! number with a 62-bit mantissa and an `i32` exponent.
+//!
+//! When `Z_n + d_n` gets closer to 0 than `d_n`, or when the reference orbit
+//! ends because `C` escaped, `d` would lose its precision. The pixel then
+//! starts over from `Z_0` with `d = Z_n + d_n` ("rebasing").
+//!
+//! Iteration counts are the ones of `kernel`: its `z_0 = c` is `Z_1 + d_1`.
+//! The interior checks and cycle detection of `kernel` do not apply, so points
+//! of the set run all `max_iter` iterations.
+
+use crate::{
+    kernel::{self, Escape, Map, BAILOUT, FRAC_BITS, MAX_COORDINATE},
+    BigFixedPoint, FixedPoint,
+};
+use alloc::{vec, vec::Vec};
+use core::ops::{Add, Mul, Neg, Sub};
+use num_bigint::BigInt;
+use parity_scale_codec::{Decode, Encode};
+use scale_info::TypeInfo;
+
+/// Decimal digits of the values of a reference orbit.
+pub const ORBIT_SCALE: u32 = 18;
+
+/// The orbit of the reference point `C` of a deep-zoom job, sent to the
+/// checkers with its pixels.
+#[derive(Encode, Decode, TypeInfo, Clone, Debug, PartialEq, Eq)]
+pub struct ReferenceOrbit {
+    /// Pixel coordinates are offsets from `C` in units of `10^-offset_scale`.
+    pub offset_scale: u32,
+    /// `Z_0 = 0, Z_1 = C, ...` up to the first value outside the disc of
+    /// radius 2, at most `max_iter + 1` values.
+    pub orbit: Vec<(FixedPoint, FixedPoint)>,
+}
+
+/// Computes the orbit of `center_re + center_im * i` with enough binary digits
+/// for the decimal digits of the center.
+pub fn reference_orbit(
+    center_re: &BigFixedPoint,
+    center_im: &BigFixedPoint,
+    offset_scale: u32,
+    max_iter: u32,
+) -> ReferenceOrbit {
+    // log2(10) < 10 / 3, plus room for the rounding errors of the iterations.
+    let bits = center_re.scale.max(center_im.scale) * 10 / 3 + 64;
+    let to_binary = |value: &BigFixedPoint| {
+        (value.to_bigint() << bits as usize) / BigInt::from(10u32).pow(value.scale)
+    };
+    let to_fixed_point = |value: &BigInt| {
+        let num = (value * BigInt::from(10u32).pow(ORBIT_SCALE)) >> bits as usize;
+        FixedPoint {
+            num: i64::try_from(&num).unwrap_or(if num.sign() == num_bigint::Sign::Minus {
+                i64::MIN
+            } else {
+                i64::MAX
+            }),
+            scale: ORBIT_SCALE,
+        }
+    };
+
+    let (c_re, c_im) = (to_binary(center_re), to_binary(center_im));
+    let bailout = BigInt::from(4u32) << bits as usize;
+    let zero = FixedPoint {
+        num: 0,
+        scale: ORBIT_SCALE,
+    };
+    let mut orbit = vec![(zero.clone(), zero)];
+    let (mut z_re, mut z_im) = (BigInt::default(), BigInt::default());
+    for _ in 0..max_iter {
+        let re_squared = &z_re * &z_re;
+        let im_squared = &z_im * &z_im;
+        let new_re = ((re_squared - im_squared) >> bits as usize) + &c_re;
+        z_im = ((&z_re * &z_im) >> (bits as usize - 1)) + &c_im;
+        z_re = new_re;
+        orbit.push((to_fixed_point(&z_re), to_fixed_point(&z_im)));
+
+        if (&z_re * &z_re + &z_im * &z_im) >> bits as usize > bailout {
+            break;
+        }
+    }
+
+    ReferenceOrbit {
+        offset_scale,
+        orbit,
+    }
+}
+
+/// Software floating-point number `mantissa * 2^exponent`. Mantissas other than
+/// 0 are normalized to `2^61 <= |mantissa| < 2^62`.
+#[derive(Clone, Copy)]
+struct Float {
+    mantissa: i64,
+    exponent: i32,
+}
+
+const MANTISSA_BITS: i32 = 62;
+
+impl Float {
+    const ZERO: Float = Float {
+        mantissa: 0,
+        exponent: 0,
+    };
+
+    fn new(mantissa: i128, exponent: i32) -> Self {
+        if mantissa == 0 {
+            return Self::ZERO;
+        }
+        let shift = 128 - mantissa.unsigned_abs().leading_zeros() as i32 - MANTISSA_BITS;
+        let mantissa = if shift > 0 {
+            mantissa >> shift
+        } else {
+            mantissa << -shift
+        };
+        Self {
+            mantissa: mantissa as i64,
+            exponent: exponent + shift,
+        }
+    }
+
+    /// `num * 10^-scale`.
+    fn from_decimal(num: i64, mut scale: u32) -> Self {
+        let mut value = Self::new(num as i128, 0);
+        while scale > 0 && value.mantissa != 0 {
+            let digits = scale.min(19);
+            value = Self::new(
+                ((value.mantissa as i128) << 64) / 10i128.pow(digits),
+                value.exponent - 64,
+            );
+            scale -= digits;
+        }
+        value
+    }
+
+    /// The value in Q4.59, saturated at 256 in magnitude.
+    fn to_q59(self) -> i128 {
+        let limit = 1i128 << (FRAC_BITS + 8);
+        let shift = self.exponent + FRAC_BITS as i32;
+        let value = self.mantissa as i128;
+        if shift >= 0 {
+            if shift > 64 {
+                return value.signum() * limit;
+            }
+            (value << shift).clamp(-limit, limit)
+        } else if shift > -64 {
+            value >> -shift
+        } else {
+            0
+        }
+    }
+
+    fn double(self) -> Self {
+        if self.mantissa == 0 {
+            return self;
+        }
+        Self {
+            mantissa: self.mantissa,
+            exponent: self.exponent + 1,
+        }
+    }
+
+    /// Compares two non-negative numbers.
+    fn is_less_than(self, other: Self) -> bool {
+        if self.mantissa == 0 || other.mantissa == 0 {
+            return self.mantissa == 0 && other.mantissa != 0;
+        }
+        (self.exponent, self.mantissa) < (other.exponent, other.mantissa)
+    }
+}
+
+impl Add for Float {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        if self.mantissa == 0 {
+            return other;
+        }
+        if other.mantissa == 0 {
+            return self;
+        }
+        let (big, small) = if self.exponent >= other.exponent {
+            (self, other)
+        } else {
+            (other, self)
+        };
+        let shift = big.exponent - small.exponent;
+        if shift > 64 {
+            return big;
+        }
+        Self::new(
+            ((big.mantissa as i128) << shift) + small.mantissa as i128,
+            small.exponent,
+        )
+    }
+}
+
+impl Neg for Float {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Self {
+            mantissa: -self.mantissa,
+            exponent: self.exponent,
+        }
+    }
+}
+
+impl Sub for Float {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        self + -other
+    }
+}
+
+impl Mul for Float {
+    type Output = Self;
+
+    fn mul(self, other: Self) -> Self {
+        Self::new(
+            self.mantissa as i128 * other.mantissa as i128,
+            self.exponent + other.exponent,
+        )
+    }
+}
+
+/// A reference orbit ready to iterate pixels against.
+pub struct Perturbation {
+    orbit: Vec<(Float, Float)>,
+    offset_scale: u32,
+}
+
+impl Perturbation {
+    pub fn new(reference: &ReferenceOrbit) -> Self {
+        let to_float = |value: &FixedPoint| Float::from_decimal(value.num, value.scale);
+        Self {
+            orbit: reference
+                .orbit
+                .iter()
+                .map(|(re, im)| (to_float(re), to_float(im)))
+                .collect(),
+            offset_scale: reference.offset_scale,
+        }
+    }
+
+    fn offset(&self, value: &FixedPoint) -> Float {
+        Float::from_decimal(value.num, value.scale.saturating_add(self.offset_scale))
+    }
+
+    /// `kernel::escape_time` of the pixel at the given offsets from the reference point.
+    pub fn escape_time(
+        &self,
+        offset_re: &FixedPoint,
+        offset_im: &FixedPoint,
+        max_iter: u32,
+    ) -> u32 {
+        self.escape(self.offset(offset_re), self.offset(offset_im), max_iter)
+            .map_or(max_iter, |escape| escape.iter)
+    }
+
+    /// `kernel::smooth_escape_time` of the pixel at the given offsets from the
+    /// reference point.
+    pub fn smooth_escape_time(
+        &self,
+        offset_re: &FixedPoint,
+        offset_im: &FixedPoint,
+        max_iter: u32,
+    ) -> (u32, FixedPoint) {
+        let (dc_re, dc_im) = (self.offset(offset_re), self.offset(offset_im));
+        let Some(escape) = self.escape(dc_re, dc_im, max_iter) else {
+            return (max_iter, kernel::bounded_smooth_iterations(max_iter));
+        };
+        // The escaped orbit only needs the pixel in low precision.
+        let (c_re, c_im) = self
+            .orbit
+            .get(1)
+            .copied()
+            .unwrap_or((Float::ZERO, Float::ZERO));
+        let (c_re, c_im) = ((c_re + dc_re).to_q59(), (c_im + dc_im).to_q59());
+        let smooth = kernel::smooth_iterations(
+            Map::Square,
+            c_re.clamp(-MAX_COORDINATE, MAX_COORDINATE) as i64,
+            c_im.clamp(-MAX_COORDINATE, MAX_COORDINATE) as i64,
+            &escape,
+            max_iter,
+        );
+        (escape.iter, smooth)
+    }
+
+    fn escape(&self, dc_re: Float, dc_im: Float, max_iter: u32) -> Option<Escape> {
+        // An orbit without `C` is malformed.
+        let last = self.orbit.len().checked_sub(1).filter(|&last| last > 0)?;
+
+        let (mut d_re, mut d_im) = (Float::ZERO, Float::ZERO);
+        let mut n = 0;
+        for i in 0..max_iter {
+            let (z_re, z_im) = self.orbit[n];
+            let new_re = (z_re * d_re - z_im * d_im).double() + d_re * d_re - d_im * d_im + dc_re;
+            d_im = (z_re * d_im + z_im * d_re).double() + (d_re * d_im).double() + dc_im;
+            d_re = new_re;
+            n += 1;
+
+            let (z_re, z_im) = self.orbit[n];
+            let (w_re, w_im) = (z_re + d_re, z_im + d_im);
+            let (q_re, q_im) = (w_re.to_q59(), w_im.to_q59());
+            if q_re.abs() > MAX_COORDINATE
+                || q_im.abs() > MAX_COORDINATE
+                || (q_re * q_re + q_im * q_im) >> FRAC_BITS > BAILOUT
+            {
+                return Some(Escape::new(i, q_re, q_im));
+            }
+
+            if n == last || (w_re * w_re + w_im * w_im).is_less_than(d_re * d_re + d_im * d_im) {
+                (d_re, d_im) = (w_re, w_im);
+                n = 0;
+            }
+        }
+
+        None
+    }
+}
@@ -0,0 +1,62 @@
+use mandelbrot_common::{
+    capabilities::{Arithmetic, PROTOCOL_VERSION},
+    fractal::FractalKind,
+    Capabilities, FixedPoint, Fractal,
+};
+
+fn mandelbrot_only() -> Capabilities {
+    Capabilities {
+        version: PROTOCOL_VERSION,
+        fractals: vec![FractalKind::Mandelbrot],
+        arithmetic: vec![Arithmetic::FixedPoint],
+        max_batch_size: 100,
+        max_iter: 1000,
+    }
+}
+
+#[test]
+fn compatibility() {
+    assert!(mandelbrot_only().is_compatible());
+    assert!(!Capabilities {
+        version: PROTOCOL_VERSION + 1,
+        ..mandelbrot_only()
+    }
+    .is_compatible());
+    assert!(!Capabilities {
+        fractals: vec![FractalKind::Julia],
+        ..mandelbrot_only()
+    }
+    .is_compatible());
+    assert!(!Capabilities {
+        arithmetic: vec![Arithmetic::Perturbation],
+        ..mandelbrot_only()
+    }
+    .is_compatible());
+    assert!(!Capabilities {
+        max_batch_size: 0,
+        ..mandelbrot_only()
+    }
+    .is_compatible());
+}
+
+#[test]
+fn supported_jobs() {
+    let capabilities = mandelbrot_only();
+    let julia = Fractal::Julia {
+        c_re: FixedPoint { num: 0, scale: 0 },
+        c_im: FixedPoint { num: 0, scale: 0 },
+    };
+    assert!(capabilities.supports(&Fractal::Mandelbrot, false, 1000));
+    assert!(!capabilities.supports(&Fractal::Mandelbrot, false, 1001));
+    assert!(!capabilities.supports(&Fractal::Mandelbrot, true, 1000));
+    assert!(!capabilities.supports(&julia, false, 1000));
+
+    let capabilities = Capabilities {
+        fractals: vec![FractalKind::Mandelbrot, FractalKind::Julia],
+        arithmetic: vec![Arithmetic::FixedPoint, Arithmetic::Perturbation],
+        ..mandelbrot_only()
+    };
+    assert!(capabilities.supports(&Fractal::Mandelbrot, true, 1000));
+    assert!(capabilities.supports(&julia, false, 1000));
+    assert!(!capabilities.supports(&Fractal::BurningShip, false, 1000));
+}
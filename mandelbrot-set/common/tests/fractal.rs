@@ -0,0 +1,132 @@
+use mandelbrot_common::{
+    fractal::MAX_MULTIBROT_POWER,
+    kernel::{escape_time, smooth_escape_time},
+    FixedPoint, Fractal, Viewport,
+};
+
+const MAX_ITER: u32 = 200;
+
+fn point(num: i64, scale: u32) -> FixedPoint {
+    FixedPoint { num, scale }
+}
+
+fn grid() -> Viewport {
+    Viewport::new(
+        60,
+        40,
+        &point(-2, 0),
+        &point(2, 0),
+        &point(-15, 1),
+        &point(15, 1),
+    )
+}
+
+#[test]
+fn validation() {
+    assert!(Fractal::Mandelbrot.is_valid());
+    assert!(Fractal::BurningShip.is_valid());
+    assert!(Fractal::Multibrot { power: 2 }.is_valid());
+    assert!(Fractal::Multibrot {
+        power: MAX_MULTIBROT_POWER
+    }
+    .is_valid());
+    assert!(!Fractal::Multibrot { power: 1 }.is_valid());
+    assert!(!Fractal::Multibrot {
+        power: MAX_MULTIBROT_POWER + 1
+    }
+    .is_valid());
+    assert!(Fractal::Julia {
+        c_re: point(-8, 1),
+        c_im: point(156, 3),
+    }
+    .is_valid());
+    // Both parts are at most 2, but not the magnitude.
+    assert!(!Fractal::Julia {
+        c_re: point(2, 0),
+        c_im: point(1, 0),
+    }
+    .is_valid());
+    assert!(!Fractal::Julia {
+        c_re: point(1, 40),
+        c_im: point(0, 0),
+    }
+    .is_valid());
+}
+
+#[test]
+fn multibrot_of_power_two_is_mandelbrot() {
+    let grid = grid();
+    for index in 0..grid.total_points() {
+        let (c_re, c_im) = grid.coordinates(index);
+        assert_eq!(
+            escape_time(&Fractal::Multibrot { power: 2 }, &c_re, &c_im, MAX_ITER),
+            escape_time(&Fractal::Mandelbrot, &c_re, &c_im, MAX_ITER),
+        );
+    }
+}
+
+#[test]
+fn multibrot() {
+    let cubic = Fractal::Multibrot { power: 3 };
+    let zero = point(0, 0);
+    assert_eq!(escape_time(&cubic, &zero, &zero, MAX_ITER), MAX_ITER);
+    // 1, 2, 9
+    assert_eq!(escape_time(&cubic, &point(1, 0), &zero, MAX_ITER), 2);
+    // -1, -2, -9, while -1 is a fixed point of the Mandelbrot map
+    assert_eq!(escape_time(&cubic, &point(-1, 0), &zero, MAX_ITER), 2);
+
+    // The step after 2 is far outside Q4.59 for the highest power.
+    let highest = Fractal::Multibrot {
+        power: MAX_MULTIBROT_POWER,
+    };
+    assert_eq!(escape_time(&highest, &point(15, 1), &zero, MAX_ITER), 1);
+    assert_eq!(escape_time(&highest, &point(15, 1), &zero, 1), 1);
+    let (iter, smooth) = smooth_escape_time(&highest, &point(15, 1), &zero, MAX_ITER);
+    assert_eq!(iter, 1);
+    assert!(smooth.num > 0 && smooth.num < 2 * 10i64.pow(smooth.scale));
+}
+
+#[test]
+fn julia() {
+    // The Julia set of 0 is the unit disc.
+    let disc = Fractal::Julia {
+        c_re: point(0, 0),
+        c_im: point(0, 0),
+    };
+    assert_eq!(
+        escape_time(&disc, &point(5, 1), &point(-5, 1), MAX_ITER),
+        MAX_ITER
+    );
+    assert!(escape_time(&disc, &point(8, 1), &point(8, 1), MAX_ITER) < MAX_ITER);
+
+    // Starting at the parameter, the orbit is the one of the Mandelbrot map.
+    let grid = grid();
+    for index in (0..grid.total_points()).step_by(7) {
+        let (c_re, c_im) = grid.coordinates(index);
+        let julia = Fractal::Julia {
+            c_re: c_re.clone(),
+            c_im: c_im.clone(),
+        };
+        assert_eq!(
+            escape_time(&julia, &c_re, &c_im, MAX_ITER),
+            escape_time(&Fractal::Mandelbrot, &c_re, &c_im, MAX_ITER),
+        );
+    }
+}
+
+#[test]
+fn burning_ship() {
+    let grid = grid();
+    let mut differs = false;
+    for index in 0..grid.total_points() {
+        let (c_re, c_im) = grid.coordinates(index);
+        let ship = escape_time(&Fractal::BurningShip, &c_re, &c_im, MAX_ITER);
+        let mandelbrot = escape_time(&Fractal::Mandelbrot, &c_re, &c_im, MAX_ITER);
+        // Orbits of real points stay real, where both maps are the same.
+        if c_im.num == 0 {
+            assert_eq!(ship, mandelbrot);
+        }
+        differs |= ship != mandelbrot;
+    }
+    assert!(differs);
+}
@@ -0,0 +1,117 @@
+use mandelbrot_common::{
+    grid::{pixel_index, pixel_position},
+    FixedPoint, Viewport,
+};
+use rust_decimal::Decimal;
+
+const WIDTH: u32 = 800;
+const HEIGHT: u32 = 600;
+
+fn viewport() -> Viewport {
+    Viewport::new(
+        WIDTH,
+        HEIGHT,
+        &FixedPoint { num: -2, scale: 0 },
+        &FixedPoint { num: 2, scale: 0 },
+        &FixedPoint { num: -15, scale: 1 },
+        &FixedPoint { num: 15, scale: 1 },
+    )
+}
+
+#[test]
+fn row_major_indexing() {
+    assert_eq!(pixel_position(0, WIDTH), (0, 0));
+    assert_eq!(pixel_position(WIDTH - 1, WIDTH), (WIDTH - 1, 0));
+    assert_eq!(pixel_position(WIDTH, WIDTH), (0, 1));
+    assert_eq!(
+        pixel_position(WIDTH * HEIGHT - 1, WIDTH),
+        (WIDTH - 1, HEIGHT - 1)
+    );
+
+    for index in (0..WIDTH * HEIGHT).step_by(997) {
+        let (column, row) = pixel_position(index, WIDTH);
+        assert!(column < WIDTH && row < HEIGHT);
+        assert_eq!(pixel_index(column, row, WIDTH), index);
+    }
+}
+
+#[test]
+fn non_square_coordinates() {
+    let viewport = viewport();
+    assert_eq!(viewport.total_points(), 480_000);
+
+    // The step is 4 / 800 = 0.005 on the real axis and 3 / 600 = 0.005 on the imaginary one
+    let cases = [
+        (0, (-2, 0), (15, 1)),
+        (WIDTH - 1, (1995, 3), (15, 1)),
+        (WIDTH, (-2, 0), (1495, 3)),
+        (pixel_index(400, 300, WIDTH), (0, 0), (0, 0)),
+        (WIDTH * HEIGHT - 1, (1995, 3), (-1495, 3)),
+    ];
+    for (index, (re_num, re_scale), (im_num, im_scale)) in cases {
+        let (c_re, c_im) = viewport.coordinates(index);
+        assert_eq!(
+            c_re.to_decimal(),
+            Decimal::new(re_num, re_scale),
+            "index {index}"
+        );
+        assert_eq!(
+            c_im.to_decimal(),
+            Decimal::new(im_num, im_scale),
+            "index {index}"
+        );
+    }
+}
+
+#[test]
+fn columns_share_real_part_and_rows_share_imaginary_part() {
+    let viewport = viewport();
+    for row in [0, 1, 299, HEIGHT - 1] {
+        let (_, first_im) = viewport.decimal_coordinates(pixel_index(0, row, WIDTH));
+        for column in [1, 400, WIDTH - 1] {
+            let (_, im) = viewport.decimal_coordinates(pixel_index(column, row, WIDTH));
+            assert_eq!(im, first_im);
+        }
+    }
+    for column in [0, 1, 399, WIDTH - 1] {
+        let (first_re, _) = viewport.decimal_coordinates(pixel_index(column, 0, WIDTH));
+        for row in [1, 300, HEIGHT - 1] {
+            let (re, _) = viewport.decimal_coordinates(pixel_index(column, row, WIDTH));
+            assert_eq!(re, first_re);
+        }
+    }
+}
+
+#[test]
+fn fixed_point_fits_into_i64() {
+    let third = Decimal::ONE / Decimal::from(3);
+    let fixed = FixedPoint::from_decimal(third);
+    assert!(fixed.scale <= 28);
+    assert!((fixed.to_decimal() - third).abs() < Decimal::new(1, 17));
+
+    assert_eq!(
+        FixedPoint::from_decimal(Decimal::new(1500, 3)),
+        FixedPoint { num: 15, scale: 1 }
+    );
+}
+
+#[test]
+fn fixed_point_saturates_large_integers() {
+    assert_eq!(
+        FixedPoint::from_decimal(Decimal::MAX),
+        FixedPoint {
+            num: i64::MAX,
+            scale: 0
+        }
+    );
+    assert_eq!(
+        FixedPoint::from_decimal(Decimal::MIN),
+        FixedPoint {
+            num: i64::MIN,
+            scale: 0
+        }
+    );
+    // Rounding a fraction may leave an integer that does not fit either.
+    let large = Decimal::from(i64::MAX) * Decimal::from(4) + Decimal::new(5, 1);
+    assert_eq!(FixedPoint::from_decimal(large).num, i64::MAX);
+}
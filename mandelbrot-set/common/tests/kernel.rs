@@ -0,0 +1,131 @@
+use mandelbrot_common::{
+    kernel::{escape_time, escape_time_decimal, smooth_escape_time},
+    FixedPoint, Fractal, Viewport,
+};
+
+const MAX_ITER: u32 = 1000;
+const MANDELBROT: Fractal = Fractal::Mandelbrot;
+
+/// The default view of the manager at a low resolution.
+fn reference_grid() -> Viewport {
+    Viewport::new(
+        120,
+        80,
+        &FixedPoint { num: -2, scale: 0 },
+        &FixedPoint { num: 1, scale: 0 },
+        &FixedPoint { num: -1, scale: 0 },
+        &FixedPoint { num: 1, scale: 0 },
+    )
+}
+
+/// Orbits of points on the boundary of the set are chaotic: the rounding errors
+/// of any two number formats grow until the escape iterations differ. Such
+/// points are rare on a regular grid.
+#[test]
+fn matches_decimal_on_reference_grid() {
+    let grid = reference_grid();
+    let mismatches: Vec<u32> = (0..grid.total_points())
+        .filter(|&index| {
+            let (c_re, c_im) = grid.coordinates(index);
+            escape_time(&MANDELBROT, &c_re, &c_im, MAX_ITER)
+                != escape_time_decimal(c_re.to_decimal(), c_im.to_decimal(), MAX_ITER)
+        })
+        .collect();
+    assert!(
+        mismatches.len() as u32 <= grid.total_points() / 1000,
+        "iteration counts differ at {mismatches:?}"
+    );
+}
+
+#[test]
+fn known_points() {
+    let point = |num, scale| FixedPoint { num, scale };
+    // The origin and c = -1 never escape.
+    assert_eq!(
+        escape_time(&MANDELBROT, &point(0, 0), &point(0, 0), MAX_ITER),
+        MAX_ITER
+    );
+    assert_eq!(
+        escape_time(&MANDELBROT, &point(-1, 0), &point(0, 0), MAX_ITER),
+        MAX_ITER
+    );
+    // |c|^2 = 4 is not above the bailout, its next value 6 is.
+    assert_eq!(
+        escape_time(&MANDELBROT, &point(2, 0), &point(0, 0), MAX_ITER),
+        1
+    );
+    assert_eq!(
+        escape_time(&MANDELBROT, &point(-2, 0), &point(0, 0), MAX_ITER),
+        MAX_ITER
+    );
+    // Points far outside escape at once, whatever their magnitude.
+    assert_eq!(
+        escape_time(&MANDELBROT, &point(i64::MAX, 0), &point(0, 0), MAX_ITER),
+        0
+    );
+    assert_eq!(
+        escape_time(&MANDELBROT, &point(0, 0), &point(-21, 1), MAX_ITER),
+        0
+    );
+    // Values below the resolution of the format are zero.
+    assert_eq!(
+        escape_time(&MANDELBROT, &point(1, 40), &point(-1, 40), MAX_ITER),
+        MAX_ITER
+    );
+    assert_eq!(escape_time(&MANDELBROT, &point(1, 0), &point(1, 0), 0), 0);
+}
+
+/// Without the early exits these points would run for `u32::MAX` iterations.
+#[test]
+fn interior_points_exit_early() {
+    let point = |num, scale| FixedPoint { num, scale };
+    let interior = [
+        // Main cardioid.
+        (point(0, 0), point(0, 0)),
+        (point(-5, 1), point(5, 1)),
+        (point(249, 3), point(0, 0)),
+        // Period-2 bulb.
+        (point(-1, 0), point(0, 0)),
+        (point(-11, 1), point(1, 1)),
+        // Period-3 bulbs, found by cycle detection.
+        (point(-1755, 3), point(0, 0)),
+        (point(-12, 2), point(74, 2)),
+        // Period-4 bulb.
+        (point(-131, 2), point(0, 0)),
+    ];
+    for (c_re, c_im) in interior {
+        assert_eq!(escape_time(&MANDELBROT, &c_re, &c_im, u32::MAX), u32::MAX);
+    }
+}
+
+fn smooth_value(smooth: &FixedPoint) -> f64 {
+    smooth.num as f64 / 10f64.powi(smooth.scale as i32)
+}
+
+#[test]
+fn smooth_iterations() {
+    let point = |num, scale| FixedPoint { num, scale };
+    let zero = point(0, 0);
+
+    // On the real axis beyond the cardioid the orbits stay real and escape
+    // sooner the further the point is: the smooth counts decrease steadily,
+    // also between points with the same integer count.
+    let mut previous = f64::MAX;
+    for num in 260..=2000 {
+        let c_re = point(num, 3);
+        let (iter, smooth) = smooth_escape_time(&MANDELBROT, &c_re, &zero, MAX_ITER);
+        assert_eq!(iter, escape_time(&MANDELBROT, &c_re, &zero, MAX_ITER));
+        let smooth = smooth_value(&smooth);
+        assert!(smooth < previous, "{c_re:?}: {smooth} after {previous}");
+        assert!(
+            (smooth - iter as f64).abs() < 3.0,
+            "{c_re:?}: {smooth} for {iter}"
+        );
+        previous = smooth;
+    }
+
+    let (iter, smooth) = smooth_escape_time(&MANDELBROT, &zero, &zero, MAX_ITER);
+    assert_eq!((iter, smooth_value(&smooth)), (MAX_ITER, MAX_ITER as f64));
+    let (iter, smooth) = smooth_escape_time(&MANDELBROT, &point(3, 0), &zero, MAX_ITER);
+    assert_eq!((iter, smooth_value(&smooth)), (0, 0.0));
+}
@@ -0,0 +1,123 @@
+use mandelbrot_common::{
+    kernel::escape_time,
+    perturbation::{reference_orbit, Perturbation},
+    BigFixedPoint, FixedPoint, Fractal, Viewport,
+};
+use num_bigint::BigInt;
+use rust_decimal::Decimal;
+
+fn big(digits: &str, scale: u32) -> BigFixedPoint {
+    BigFixedPoint::new(&digits.parse().unwrap(), scale)
+}
+
+/// 8x8 offsets over [-1.5, 1.5] on both axes.
+fn offsets() -> Viewport {
+    Viewport::new(
+        8,
+        8,
+        &FixedPoint { num: -15, scale: 1 },
+        &FixedPoint { num: 15, scale: 1 },
+        &FixedPoint { num: -15, scale: 1 },
+        &FixedPoint { num: 15, scale: 1 },
+    )
+}
+
+/// Escape iteration computed directly with `bits` fractional bits.
+fn exact_escape_time(c_re: &BigInt, c_im: &BigInt, bits: usize, max_iter: u32) -> u32 {
+    let bailout = BigInt::from(4) << bits;
+    let (mut z_re, mut z_im) = (c_re.clone(), c_im.clone());
+    for i in 0..max_iter {
+        if (&z_re * &z_re + &z_im * &z_im) >> bits > bailout {
+            return i;
+        }
+        let new_re = ((&z_re * &z_re - &z_im * &z_im) >> bits) + c_re;
+        z_im = ((&z_re * &z_im) >> (bits - 1)) + c_im;
+        z_re = new_re;
+    }
+    max_iter
+}
+
+#[test]
+fn matches_kernel_at_low_zoom() {
+    // Offsets of up to 0.15 around -0.75 + 0.1i.
+    let (center_re, center_im) = (big("-75", 2), big("1", 1));
+    let max_iter = 500;
+    let perturbation = Perturbation::new(&reference_orbit(&center_re, &center_im, 1, max_iter));
+
+    let grid = Viewport::new(
+        40,
+        40,
+        &FixedPoint { num: -15, scale: 1 },
+        &FixedPoint { num: 15, scale: 1 },
+        &FixedPoint { num: -15, scale: 1 },
+        &FixedPoint { num: 15, scale: 1 },
+    );
+    let mut mismatches = 0;
+    for index in 0..grid.total_points() {
+        let (offset_re, offset_im) = grid.coordinates(index);
+        let absolute = |center: Decimal, offset: &FixedPoint| {
+            FixedPoint::from_decimal(center + offset.to_decimal() / Decimal::TEN)
+        };
+        let c_re = absolute(Decimal::new(-75, 2), &offset_re);
+        let c_im = absolute(Decimal::new(1, 1), &offset_im);
+        if perturbation.escape_time(&offset_re, &offset_im, max_iter)
+            != escape_time(&Fractal::Mandelbrot, &c_re, &c_im, max_iter)
+        {
+            mismatches += 1;
+        }
+    }
+    // Only chaotic points on the boundary may differ, see the kernel tests.
+    assert!(mismatches <= 2, "{mismatches} mismatches");
+}
+
+#[test]
+fn resolves_deep_zoom() {
+    // Next to the Misiurewicz point i, viewed 3e-31 wide.
+    let center_re = "17";
+    let center_im = "1000000000000000000000000000000003";
+    let (scale, offset_scale, max_iter) = (33, 31, 3000);
+    let perturbation = Perturbation::new(&reference_orbit(
+        &big(center_re, scale),
+        &big(center_im, scale),
+        offset_scale,
+        max_iter,
+    ));
+
+    // Pixels directly with 256 fractional bits.
+    let bits = 256;
+    let to_binary = |num: BigInt, scale: u32| (num << bits) / BigInt::from(10).pow(scale);
+    let center_re: BigInt = center_re.parse().unwrap();
+    let center_im: BigInt = center_im.parse().unwrap();
+    let grid = offsets();
+    let mut results = Vec::new();
+    for index in 0..grid.total_points() {
+        let (offset_re, offset_im) = grid.coordinates(index);
+        let absolute = |center: &BigInt, offset: &FixedPoint| {
+            // center * 10^-scale + offset * 10^-(offset.scale + offset_scale)
+            let offset_scale = offset.scale + offset_scale;
+            let total_scale = offset_scale.max(scale);
+            let num = center * BigInt::from(10).pow(total_scale - scale)
+                + offset.num * BigInt::from(10).pow(total_scale - offset_scale);
+            to_binary(num, total_scale)
+        };
+        let exact = exact_escape_time(
+            &absolute(&center_re, &offset_re),
+            &absolute(&center_im, &offset_im),
+            bits,
+            max_iter,
+        );
+        let perturbed = perturbation.escape_time(&offset_re, &offset_im, max_iter);
+        assert_eq!(perturbed, exact, "pixel {index}");
+        let (smooth_iter, smooth) =
+            perturbation.smooth_escape_time(&offset_re, &offset_im, max_iter);
+        assert_eq!(smooth_iter, perturbed);
+        let smooth = smooth.num as f64 / 10f64.powi(smooth.scale as i32);
+        assert!((smooth - perturbed as f64).abs() < 3.0);
+        results.push(perturbed);
+    }
+
+    // The pixels are told apart, unlike with the 18 digits of `FixedPoint`.
+    results.sort();
+    results.dedup();
+    assert!(results.len() > 10, "{results:?}");
+}
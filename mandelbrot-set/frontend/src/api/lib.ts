@@ -1,17 +1,143 @@
 import { GearApi, decodeAddress } from "@gear-js/api";
 import { TypeRegistry } from "@polkadot/types";
 import { RegistryTypes } from "@polkadot/types/types";
-import { TransactionBuilder, ActorId, ZERO_ADDRESS } from "sails-js";
+import {
+  TransactionBuilder,
+  ActorId,
+  CodeId,
+  ZERO_ADDRESS,
+  getServiceNamePrefix,
+  getFnNamePrefix,
+} from "sails-js";
+
+export type ManagerError =
+  | "NotOwner"
+  | "NotAdmin"
+  | "UnknownChecker"
+  | "AlreadyAdmin"
+  | "NotAnAdmin"
+  | "CheckerNotFound"
+  | "CheckerAlreadyRegistered"
+  | "InvalidResults"
+  | "JobNotFound"
+  | "NotJobCreator"
+  | "InvalidJobParams"
+  | "NotPayee"
+  | "NothingToWithdraw"
+  | "CheckerCodeNotSet"
+  | "IncompatibleChecker"
+  | "TooManyJobs"
+  | "TooManyPoints"
+  | "CheckerCreationFailed";
+
+export interface Capabilities {
+  version: number;
+  fractals: Array<FractalKind>;
+  arithmetic: Array<Arithmetic>;
+  max_batch_size: number;
+  max_iter: number;
+}
+
+export type FractalKind = "Mandelbrot" | "Julia" | "Multibrot" | "BurningShip";
+
+export type Arithmetic = "FixedPoint" | "Perturbation";
+
+export interface JobParams {
+  fractal: Fractal;
+  width: number;
+  height: number;
+  x_min: FixedPoint;
+  x_max: FixedPoint;
+  y_min: FixedPoint;
+  y_max: FixedPoint;
+  max_iter: number;
+  smooth: boolean;
+  deep_zoom: DeepZoom | null;
+  adaptive: boolean;
+}
+
+export type Fractal =
+  | { mandelbrot: null }
+  | { julia: { c_re: FixedPoint; c_im: FixedPoint } }
+  | { multibrot: { power: number } }
+  | { burningShip: null };
 
 export interface FixedPoint {
   num: number | string | bigint;
   scale: number;
 }
 
+export interface DeepZoom {
+  center_re: BigFixedPoint;
+  center_im: BigFixedPoint;
+  offset_scale: number;
+}
+
+export interface BigFixedPoint {
+  num: Array<number>;
+  scale: number;
+}
+
+export interface VerificationConfig {
+  sample_size: number;
+  max_disputes: number;
+}
+
+export interface CheckerStats {
+  batches_completed: number;
+  points_processed: number | string | bigint;
+  failures: number;
+  pending_batches: number;
+  last_active_block: number;
+  disputes_lost: number;
+}
+
+export interface Dispute {
+  job_id: number;
+  index: number;
+  checker: ActorId;
+  checker_iter: number;
+  verifier: ActorId;
+  verifier_iter: number;
+  status: DisputeStatus;
+}
+
+export type DisputeStatus =
+  | { open: null }
+  | { resolved: { faulty: ActorId } }
+  | { unresolved: null };
+
+export interface JobInfo {
+  id: number;
+  creator: ActorId;
+  params: JobParams;
+  status: JobStatus;
+  total_points: number;
+  points_generated: number;
+  points_sent: number;
+  points_checked: number;
+  points_filled: number;
+  budget: number | string | bigint;
+  reward_per_point: number | string | bigint;
+  created_at: number;
+  started_at: number | null;
+  completed_at: number | null;
+  results_version: number;
+}
+
+export type JobStatus = "Generating" | "Checking" | "Completed";
+
+export interface ResultsPage {
+  results: Array<PointResult>;
+  next_cursor: number | null;
+}
+
 export interface PointResult {
-  c_re: number | string | bigint;
-  c_im: number | string | bigint;
+  index: number;
+  c_re: FixedPoint;
+  c_im: FixedPoint;
   iter: number;
+  smooth: FixedPoint | null;
   checked: boolean;
 }
 
@@ -24,8 +150,124 @@ export class Program {
     public programId?: `0x${string}`
   ) {
     const types: RegistryTypes = {
+      ManagerError: {
+        _enum: [
+          "NotOwner",
+          "NotAdmin",
+          "UnknownChecker",
+          "AlreadyAdmin",
+          "NotAnAdmin",
+          "CheckerNotFound",
+          "CheckerAlreadyRegistered",
+          "InvalidResults",
+          "JobNotFound",
+          "NotJobCreator",
+          "InvalidJobParams",
+          "NotPayee",
+          "NothingToWithdraw",
+          "CheckerCodeNotSet",
+          "IncompatibleChecker",
+          "TooManyJobs",
+          "TooManyPoints",
+          "CheckerCreationFailed",
+        ],
+      },
+      Capabilities: {
+        version: "u32",
+        fractals: "Vec<FractalKind>",
+        arithmetic: "Vec<Arithmetic>",
+        max_batch_size: "u32",
+        max_iter: "u32",
+      },
+      FractalKind: {
+        _enum: [
+          "Mandelbrot",
+          "Julia",
+          "Multibrot",
+          "BurningShip",
+        ],
+      },
+      Arithmetic: { _enum: ["FixedPoint", "Perturbation"] },
+      JobParams: {
+        fractal: "Fractal",
+        width: "u32",
+        height: "u32",
+        x_min: "FixedPoint",
+        x_max: "FixedPoint",
+        y_min: "FixedPoint",
+        y_max: "FixedPoint",
+        max_iter: "u32",
+        smooth: "bool",
+        deep_zoom: "Option<DeepZoom>",
+        adaptive: "bool",
+      },
+      Fractal: {
+        _enum: {
+          Mandelbrot: "Null",
+          Julia: { c_re: "FixedPoint", c_im: "FixedPoint" },
+          Multibrot: { power: "u32" },
+          BurningShip: "Null",
+        },
+      },
       FixedPoint: { num: "i64", scale: "u32" },
-      PointResult: { c_re: "i128", c_im: "i128", iter: "u32", checked: "bool" },
+      DeepZoom: {
+        center_re: "BigFixedPoint",
+        center_im: "BigFixedPoint",
+        offset_scale: "u32",
+      },
+      BigFixedPoint: { num: "Vec<u8>", scale: "u32" },
+      VerificationConfig: { sample_size: "u32", max_disputes: "u32" },
+      CheckerStats: {
+        batches_completed: "u32",
+        points_processed: "u64",
+        failures: "u32",
+        pending_batches: "u32",
+        last_active_block: "u32",
+        disputes_lost: "u32",
+      },
+      Dispute: {
+        job_id: "u32",
+        index: "u32",
+        checker: "[u8;32]",
+        checker_iter: "u32",
+        verifier: "[u8;32]",
+        verifier_iter: "u32",
+        status: "DisputeStatus",
+      },
+      DisputeStatus: {
+        _enum: {
+          Open: "Null",
+          Resolved: { faulty: "[u8;32]" },
+          Unresolved: "Null",
+        },
+      },
+      JobInfo: {
+        id: "u32",
+        creator: "[u8;32]",
+        params: "JobParams",
+        status: "JobStatus",
+        total_points: "u32",
+        points_generated: "u32",
+        points_sent: "u32",
+        points_checked: "u32",
+        points_filled: "u32",
+        budget: "u128",
+        reward_per_point: "u128",
+        created_at: "u32",
+        started_at: "Option<u32>",
+        completed_at: "Option<u32>",
+        results_version: "u32",
+      },
+      JobStatus: { _enum: ["Generating", "Checking", "Completed"] },
+      ResultsPage: { results: "Vec<PointResult>", next_cursor: "Option<u32>" },
+      PointResult: {
+        index: "u32",
+        c_re: "FixedPoint",
+        c_im: "FixedPoint",
+        iter: "u32",
+        smooth: "Option<FixedPoint>",
+        checked: "bool",
+      },
     };
 
     this.registry = new TypeRegistry();
@@ -69,121 +311,509 @@ export class Program {
 export class Manager {
   constructor(private _program: Program) {}
 
-  public addCheckers(checkers: Array<ActorId>): TransactionBuilder<null> {
+  public addAdmin(
+    admin: ActorId
+  ): TransactionBuilder<{ ok: null } | { err: ManagerError }> {
+    if (!this._program.programId) throw new Error("Program ID is not set");
+    return new TransactionBuilder<{ ok: null } | { err: ManagerError }>(
+      this._program.api,
+      this._program.registry,
+      "send_message",
+      ["Manager", "AddAdmin", admin],
+      "(String, String, [u8;32])",
+      "Result<Null, ManagerError>",
+      this._program.programId
+    );
+  }
+
+  public addCheckerWithCapabilities(
+    checker: ActorId,
+    capabilities: Capabilities
+  ): TransactionBuilder<{ ok: null } | { err: ManagerError }> {
     if (!this._program.programId) throw new Error("Program ID is not set");
-    return new TransactionBuilder<null>(
+    return new TransactionBuilder<{ ok: null } | { err: ManagerError }>(
+      this._program.api,
+      this._program.registry,
+      "send_message",
+      ["Manager", "AddCheckerWithCapabilities", checker, capabilities],
+      "(String, String, [u8;32], Capabilities)",
+      "Result<Null, ManagerError>",
+      this._program.programId
+    );
+  }
+
+  public addCheckers(
+    checkers: Array<ActorId>
+  ): TransactionBuilder<{ ok: null } | { err: ManagerError }> {
+    if (!this._program.programId) throw new Error("Program ID is not set");
+    return new TransactionBuilder<{ ok: null } | { err: ManagerError }>(
       this._program.api,
       this._program.registry,
       "send_message",
       ["Manager", "AddCheckers", checkers],
       "(String, String, Vec<[u8;32]>)",
-      "Null",
+      "Result<Null, ManagerError>",
       this._program.programId
     );
   }
 
   public checkPointsSet(
-    max_iter: number,
     batch_size: number,
     continue_checking: boolean
-  ): TransactionBuilder<null> {
+  ): TransactionBuilder<{ ok: null } | { err: ManagerError }> {
+    if (!this._program.programId) throw new Error("Program ID is not set");
+    return new TransactionBuilder<{ ok: null } | { err: ManagerError }>(
+      this._program.api,
+      this._program.registry,
+      "send_message",
+      ["Manager", "CheckPointsSet", batch_size, continue_checking],
+      "(String, String, u32, bool)",
+      "Result<Null, ManagerError>",
+      this._program.programId
+    );
+  }
+
+  public createJob(
+    params: JobParams
+  ): TransactionBuilder<{ ok: number } | { err: ManagerError }> {
     if (!this._program.programId) throw new Error("Program ID is not set");
-    return new TransactionBuilder<null>(
+    return new TransactionBuilder<{ ok: number } | { err: ManagerError }>(
       this._program.api,
       this._program.registry,
       "send_message",
-      ["Manager", "CheckPointsSet", max_iter, batch_size, continue_checking],
-      "(String, String, u32, u32, bool)",
-      "Null",
+      ["Manager", "CreateJob", params],
+      "(String, String, JobParams)",
+      "Result<u32, ManagerError>",
       this._program.programId
     );
   }
 
   public generateAndStorePoints(
-    width: number,
-    height: number,
-    x_min: FixedPoint,
-    x_max: FixedPoint,
-    y_min: FixedPoint,
-    y_max: FixedPoint,
+    job_id: number,
     points_per_call: number,
     continue_generation: boolean,
     check_points_after_generation: boolean,
-    max_iter: number,
     batch_size: number
-  ): TransactionBuilder<null> {
+  ): TransactionBuilder<{ ok: null } | { err: ManagerError }> {
     if (!this._program.programId) throw new Error("Program ID is not set");
-    return new TransactionBuilder<null>(
+    return new TransactionBuilder<{ ok: null } | { err: ManagerError }>(
       this._program.api,
       this._program.registry,
       "send_message",
       [
         "Manager",
         "GenerateAndStorePoints",
-        width,
-        height,
-        x_min,
-        x_max,
-        y_min,
-        y_max,
+        job_id,
         points_per_call,
         continue_generation,
         check_points_after_generation,
-        max_iter,
         batch_size,
       ],
-      "(String, String, u32, u32, FixedPoint, FixedPoint, FixedPoint, FixedPoint, u32, bool, bool, u32, u32)",
-      "Null",
+      "(String, String, u32, u32, bool, bool, u32)",
+      "Result<Null, ManagerError>",
       this._program.programId
     );
   }
 
-  public restart(): TransactionBuilder<null> {
+  public removeAdmin(
+    admin: ActorId
+  ): TransactionBuilder<{ ok: null } | { err: ManagerError }> {
     if (!this._program.programId) throw new Error("Program ID is not set");
-    return new TransactionBuilder<null>(
+    return new TransactionBuilder<{ ok: null } | { err: ManagerError }>(
       this._program.api,
       this._program.registry,
       "send_message",
-      ["Manager", "Restart"],
-      "(String, String)",
-      "Null",
+      ["Manager", "RemoveAdmin", admin],
+      "(String, String, [u8;32])",
+      "Result<Null, ManagerError>",
+      this._program.programId
+    );
+  }
+
+  public removeCheckers(
+    checkers: Array<ActorId>
+  ): TransactionBuilder<{ ok: null } | { err: ManagerError }> {
+    if (!this._program.programId) throw new Error("Program ID is not set");
+    return new TransactionBuilder<{ ok: null } | { err: ManagerError }>(
+      this._program.api,
+      this._program.registry,
+      "send_message",
+      ["Manager", "RemoveCheckers", checkers],
+      "(String, String, Vec<[u8;32]>)",
+      "Result<Null, ManagerError>",
+      this._program.programId
+    );
+  }
+
+  public removeJob(
+    job_id: number
+  ): TransactionBuilder<{ ok: null } | { err: ManagerError }> {
+    if (!this._program.programId) throw new Error("Program ID is not set");
+    return new TransactionBuilder<{ ok: null } | { err: ManagerError }>(
+      this._program.api,
+      this._program.registry,
+      "send_message",
+      ["Manager", "RemoveJob", job_id],
+      "(String, String, u32)",
+      "Result<Null, ManagerError>",
+      this._program.programId
+    );
+  }
+
+  public replaceChecker(
+    old: ActorId,
+    new: ActorId
+  ): TransactionBuilder<{ ok: null } | { err: ManagerError }> {
+    if (!this._program.programId) throw new Error("Program ID is not set");
+    return new TransactionBuilder<{ ok: null } | { err: ManagerError }>(
+      this._program.api,
+      this._program.registry,
+      "send_message",
+      ["Manager", "ReplaceChecker", old, new],
+      "(String, String, [u8;32], [u8;32])",
+      "Result<Null, ManagerError>",
+      this._program.programId
+    );
+  }
+
+  public restartJob(
+    job_id: number
+  ): TransactionBuilder<{ ok: null } | { err: ManagerError }> {
+    if (!this._program.programId) throw new Error("Program ID is not set");
+    return new TransactionBuilder<{ ok: null } | { err: ManagerError }>(
+      this._program.api,
+      this._program.registry,
+      "send_message",
+      ["Manager", "RestartJob", job_id],
+      "(String, String, u32)",
+      "Result<Null, ManagerError>",
       this._program.programId
     );
   }
 
   public resultCalculated(
+    batch_id: number | string | bigint,
     indexes: Array<number>,
-    results: Array<number>
-  ): TransactionBuilder<null> {
+    results: Array<number>,
+    smooth: Array<FixedPoint>
+  ): TransactionBuilder<{ ok: null } | { err: ManagerError }> {
     if (!this._program.programId) throw new Error("Program ID is not set");
-    return new TransactionBuilder<null>(
+    return new TransactionBuilder<{ ok: null } | { err: ManagerError }>(
       this._program.api,
       this._program.registry,
       "send_message",
-      ["Manager", "ResultCalculated", indexes, results],
-      "(String, String, Vec<u32>, Vec<u32>)",
-      "Null",
+      ["Manager", "ResultCalculated", batch_id, indexes, results, smooth],
+      "(String, String, u64, Vec<u32>, Vec<u32>, Vec<FixedPoint>)",
+      "Result<Null, ManagerError>",
+      this._program.programId
+    );
+  }
+
+  public scaleCheckers(
+    count: number
+  ): TransactionBuilder<{ ok: null } | { err: ManagerError }> {
+    if (!this._program.programId) throw new Error("Program ID is not set");
+    return new TransactionBuilder<{ ok: null } | { err: ManagerError }>(
+      this._program.api,
+      this._program.registry,
+      "send_message",
+      ["Manager", "ScaleCheckers", count],
+      "(String, String, u32)",
+      "Result<Null, ManagerError>",
       this._program.programId
     );
   }
 
   public sendNextBatch(
     checker: ActorId,
-    max_iter: number,
     batch_size: number
-  ): TransactionBuilder<null> {
+  ): TransactionBuilder<{ ok: null } | { err: ManagerError }> {
+    if (!this._program.programId) throw new Error("Program ID is not set");
+    return new TransactionBuilder<{ ok: null } | { err: ManagerError }>(
+      this._program.api,
+      this._program.registry,
+      "send_message",
+      ["Manager", "SendNextBatch", checker, batch_size],
+      "(String, String, [u8;32], u32)",
+      "Result<Null, ManagerError>",
+      this._program.programId
+    );
+  }
+
+  public setCheckerCode(
+    code_id: CodeId
+  ): TransactionBuilder<{ ok: null } | { err: ManagerError }> {
+    if (!this._program.programId) throw new Error("Program ID is not set");
+    return new TransactionBuilder<{ ok: null } | { err: ManagerError }>(
+      this._program.api,
+      this._program.registry,
+      "send_message",
+      ["Manager", "SetCheckerCode", code_id],
+      "(String, String, [u8;32])",
+      "Result<Null, ManagerError>",
+      this._program.programId
+    );
+  }
+
+  public setPayee(
+    checker: ActorId,
+    payee: ActorId
+  ): TransactionBuilder<{ ok: null } | { err: ManagerError }> {
+    if (!this._program.programId) throw new Error("Program ID is not set");
+    return new TransactionBuilder<{ ok: null } | { err: ManagerError }>(
+      this._program.api,
+      this._program.registry,
+      "send_message",
+      ["Manager", "SetPayee", checker, payee],
+      "(String, String, [u8;32], [u8;32])",
+      "Result<Null, ManagerError>",
+      this._program.programId
+    );
+  }
+
+  public setVerification(
+    config: VerificationConfig
+  ): TransactionBuilder<{ ok: null } | { err: ManagerError }> {
     if (!this._program.programId) throw new Error("Program ID is not set");
-    return new TransactionBuilder<null>(
+    return new TransactionBuilder<{ ok: null } | { err: ManagerError }>(
       this._program.api,
       this._program.registry,
       "send_message",
-      ["Manager", "SendNextBatch", checker, max_iter, batch_size],
-      "(String, String, [u8;32], u32, u32)",
-      "Null",
+      ["Manager", "SetVerification", config],
+      "(String, String, VerificationConfig)",
+      "Result<Null, ManagerError>",
       this._program.programId
     );
   }
 
+  public spawnCheckers(
+    count: number
+  ): TransactionBuilder<{ ok: Array<ActorId> } | { err: ManagerError }> {
+    if (!this._program.programId) throw new Error("Program ID is not set");
+    return new TransactionBuilder<
+      { ok: Array<ActorId> } | { err: ManagerError }
+    >(
+      this._program.api,
+      this._program.registry,
+      "send_message",
+      ["Manager", "SpawnCheckers", count],
+      "(String, String, u32)",
+      "Result<Vec<[u8;32]>, ManagerError>",
+      this._program.programId
+    );
+  }
+
+  public transferOwnership(
+    new_owner: ActorId
+  ): TransactionBuilder<{ ok: null } | { err: ManagerError }> {
+    if (!this._program.programId) throw new Error("Program ID is not set");
+    return new TransactionBuilder<{ ok: null } | { err: ManagerError }>(
+      this._program.api,
+      this._program.registry,
+      "send_message",
+      ["Manager", "TransferOwnership", new_owner],
+      "(String, String, [u8;32])",
+      "Result<Null, ManagerError>",
+      this._program.programId
+    );
+  }
+
+  public withdraw(
+    checker: ActorId
+  ): TransactionBuilder<
+    { ok: number | string | bigint } | { err: ManagerError }
+  > {
+    if (!this._program.programId) throw new Error("Program ID is not set");
+    return new TransactionBuilder<
+      { ok: number | string | bigint } | { err: ManagerError }
+    >(
+      this._program.api,
+      this._program.registry,
+      "send_message",
+      ["Manager", "Withdraw", checker],
+      "(String, String, [u8;32])",
+      "Result<u128, ManagerError>",
+      this._program.programId
+    );
+  }
+
+  public async admins(
+    originAddress?: string,
+    value?: number | string | bigint,
+    atBlock?: `0x${string}`
+  ): Promise<Array<ActorId>> {
+    const payload = this._program.registry
+      .createType("(String, String)", ["Manager", "Admins"])
+      .toHex();
+    const reply = await this._program.api.message.calculateReply({
+      destination: this._program.programId!,
+      origin: originAddress ? decodeAddress(originAddress) : ZERO_ADDRESS,
+      payload,
+      value: value || 0,
+      gasLimit: this._program.api.blockGasLimit.toBigInt(),
+      at: atBlock,
+    });
+    if (!reply.code.isSuccess)
+      throw new Error(
+        this._program.registry.createType("String", reply.payload).toString()
+      );
+    const result = this._program.registry.createType(
+      "(String, String, Vec<[u8;32]>)",
+      reply.payload
+    );
+    return result[2].toJSON() as unknown as Array<ActorId>;
+  }
+
+  public async getAllCheckerStats(
+    originAddress?: string,
+    value?: number | string | bigint,
+    atBlock?: `0x${string}`
+  ): Promise<Array<[ActorId, CheckerStats]>> {
+    const payload = this._program.registry
+      .createType("(String, String)", ["Manager", "GetAllCheckerStats"])
+      .toHex();
+    const reply = await this._program.api.message.calculateReply({
+      destination: this._program.programId!,
+      origin: originAddress ? decodeAddress(originAddress) : ZERO_ADDRESS,
+      payload,
+      value: value || 0,
+      gasLimit: this._program.api.blockGasLimit.toBigInt(),
+      at: atBlock,
+    });
+    if (!reply.code.isSuccess)
+      throw new Error(
+        this._program.registry.createType("String", reply.payload).toString()
+      );
+    const result = this._program.registry.createType(
+      "(String, String, Vec<([u8;32], CheckerStats)>)",
+      reply.payload
+    );
+    return result[2].toJSON() as unknown as Array<[ActorId, CheckerStats]>;
+  }
+
+  public async getBalance(
+    checker: ActorId,
+    originAddress?: string,
+    value?: number | string | bigint,
+    atBlock?: `0x${string}`
+  ): Promise<bigint> {
+    const payload = this._program.registry
+      .createType("(String, String, [u8;32])", [
+        "Manager",
+        "GetBalance",
+        checker,
+      ])
+      .toHex();
+    const reply = await this._program.api.message.calculateReply({
+      destination: this._program.programId!,
+      origin: originAddress ? decodeAddress(originAddress) : ZERO_ADDRESS,
+      payload,
+      value: value || 0,
+      gasLimit: this._program.api.blockGasLimit.toBigInt(),
+      at: atBlock,
+    });
+    if (!reply.code.isSuccess)
+      throw new Error(
+        this._program.registry.createType("String", reply.payload).toString()
+      );
+    const result = this._program.registry.createType(
+      "(String, String, u128)",
+      reply.payload
+    );
+    return result[2].toBigInt() as unknown as bigint;
+  }
+
+  public async getCheckerCapabilities(
+    checker: ActorId,
+    originAddress?: string,
+    value?: number | string | bigint,
+    atBlock?: `0x${string}`
+  ): Promise<Capabilities | null> {
+    const payload = this._program.registry
+      .createType("(String, String, [u8;32])", [
+        "Manager",
+        "GetCheckerCapabilities",
+        checker,
+      ])
+      .toHex();
+    const reply = await this._program.api.message.calculateReply({
+      destination: this._program.programId!,
+      origin: originAddress ? decodeAddress(originAddress) : ZERO_ADDRESS,
+      payload,
+      value: value || 0,
+      gasLimit: this._program.api.blockGasLimit.toBigInt(),
+      at: atBlock,
+    });
+    if (!reply.code.isSuccess)
+      throw new Error(
+        this._program.registry.createType("String", reply.payload).toString()
+      );
+    const result = this._program.registry.createType(
+      "(String, String, Option<Capabilities>)",
+      reply.payload
+    );
+    return result[2].toJSON() as unknown as Capabilities | null;
+  }
+
+  public async getCheckerCode(
+    originAddress?: string,
+    value?: number | string | bigint,
+    atBlock?: `0x${string}`
+  ): Promise<CodeId | null> {
+    const payload = this._program.registry
+      .createType("(String, String)", ["Manager", "GetCheckerCode"])
+      .toHex();
+    const reply = await this._program.api.message.calculateReply({
+      destination: this._program.programId!,
+      origin: originAddress ? decodeAddress(originAddress) : ZERO_ADDRESS,
+      payload,
+      value: value || 0,
+      gasLimit: this._program.api.blockGasLimit.toBigInt(),
+      at: atBlock,
+    });
+    if (!reply.code.isSuccess)
+      throw new Error(
+        this._program.registry.createType("String", reply.payload).toString()
+      );
+    const result = this._program.registry.createType(
+      "(String, String, Option<[u8;32]>)",
+      reply.payload
+    );
+    return result[2].toJSON() as unknown as CodeId | null;
+  }
+
+  public async getCheckerStats(
+    checker: ActorId,
+    originAddress?: string,
+    value?: number | string | bigint,
+    atBlock?: `0x${string}`
+  ): Promise<CheckerStats | null> {
+    const payload = this._program.registry
+      .createType("(String, String, [u8;32])", [
+        "Manager",
+        "GetCheckerStats",
+        checker,
+      ])
+      .toHex();
+    const reply = await this._program.api.message.calculateReply({
+      destination: this._program.programId!,
+      origin: originAddress ? decodeAddress(originAddress) : ZERO_ADDRESS,
+      payload,
+      value: value || 0,
+      gasLimit: this._program.api.blockGasLimit.toBigInt(),
+      at: atBlock,
+    });
+    if (!reply.code.isSuccess)
+      throw new Error(
+        this._program.registry.createType("String", reply.payload).toString()
+      );
+    const result = this._program.registry.createType(
+      "(String, String, Option<CheckerStats>)",
+      reply.payload
+    );
+    return result[2].toJSON() as unknown as CheckerStats | null;
+  }
+
   public async getCheckers(
     originAddress?: string,
     value?: number | string | bigint,
@@ -211,13 +841,153 @@ export class Manager {
     return result[2].toJSON() as unknown as Array<ActorId>;
   }
 
+  public async getDisputes(
+    job_id: number,
+    originAddress?: string,
+    value?: number | string | bigint,
+    atBlock?: `0x${string}`
+  ): Promise<Array<Dispute>> {
+    const payload = this._program.registry
+      .createType("(String, String, u32)", ["Manager", "GetDisputes", job_id])
+      .toHex();
+    const reply = await this._program.api.message.calculateReply({
+      destination: this._program.programId!,
+      origin: originAddress ? decodeAddress(originAddress) : ZERO_ADDRESS,
+      payload,
+      value: value || 0,
+      gasLimit: this._program.api.blockGasLimit.toBigInt(),
+      at: atBlock,
+    });
+    if (!reply.code.isSuccess)
+      throw new Error(
+        this._program.registry.createType("String", reply.payload).toString()
+      );
+    const result = this._program.registry.createType(
+      "(String, String, Vec<Dispute>)",
+      reply.payload
+    );
+    return result[2].toJSON() as unknown as Array<Dispute>;
+  }
+
+  public async getJob(
+    job_id: number,
+    originAddress?: string,
+    value?: number | string | bigint,
+    atBlock?: `0x${string}`
+  ): Promise<JobInfo | null> {
+    const payload = this._program.registry
+      .createType("(String, String, u32)", ["Manager", "GetJob", job_id])
+      .toHex();
+    const reply = await this._program.api.message.calculateReply({
+      destination: this._program.programId!,
+      origin: originAddress ? decodeAddress(originAddress) : ZERO_ADDRESS,
+      payload,
+      value: value || 0,
+      gasLimit: this._program.api.blockGasLimit.toBigInt(),
+      at: atBlock,
+    });
+    if (!reply.code.isSuccess)
+      throw new Error(
+        this._program.registry.createType("String", reply.payload).toString()
+      );
+    const result = this._program.registry.createType(
+      "(String, String, Option<JobInfo>)",
+      reply.payload
+    );
+    return result[2].toJSON() as unknown as JobInfo | null;
+  }
+
+  public async getJobParams(
+    job_id: number,
+    originAddress?: string,
+    value?: number | string | bigint,
+    atBlock?: `0x${string}`
+  ): Promise<JobParams | null> {
+    const payload = this._program.registry
+      .createType("(String, String, u32)", ["Manager", "GetJobParams", job_id])
+      .toHex();
+    const reply = await this._program.api.message.calculateReply({
+      destination: this._program.programId!,
+      origin: originAddress ? decodeAddress(originAddress) : ZERO_ADDRESS,
+      payload,
+      value: value || 0,
+      gasLimit: this._program.api.blockGasLimit.toBigInt(),
+      at: atBlock,
+    });
+    if (!reply.code.isSuccess)
+      throw new Error(
+        this._program.registry.createType("String", reply.payload).toString()
+      );
+    const result = this._program.registry.createType(
+      "(String, String, Option<JobParams>)",
+      reply.payload
+    );
+    return result[2].toJSON() as unknown as JobParams | null;
+  }
+
+  public async getJobs(
+    originAddress?: string,
+    value?: number | string | bigint,
+    atBlock?: `0x${string}`
+  ): Promise<Array<JobInfo>> {
+    const payload = this._program.registry
+      .createType("(String, String)", ["Manager", "GetJobs"])
+      .toHex();
+    const reply = await this._program.api.message.calculateReply({
+      destination: this._program.programId!,
+      origin: originAddress ? decodeAddress(originAddress) : ZERO_ADDRESS,
+      payload,
+      value: value || 0,
+      gasLimit: this._program.api.blockGasLimit.toBigInt(),
+      at: atBlock,
+    });
+    if (!reply.code.isSuccess)
+      throw new Error(
+        this._program.registry.createType("String", reply.payload).toString()
+      );
+    const result = this._program.registry.createType(
+      "(String, String, Vec<JobInfo>)",
+      reply.payload
+    );
+    return result[2].toJSON() as unknown as Array<JobInfo>;
+  }
+
+  public async getPayee(
+    checker: ActorId,
+    originAddress?: string,
+    value?: number | string | bigint,
+    atBlock?: `0x${string}`
+  ): Promise<ActorId | null> {
+    const payload = this._program.registry
+      .createType("(String, String, [u8;32])", ["Manager", "GetPayee", checker])
+      .toHex();
+    const reply = await this._program.api.message.calculateReply({
+      destination: this._program.programId!,
+      origin: originAddress ? decodeAddress(originAddress) : ZERO_ADDRESS,
+      payload,
+      value: value || 0,
+      gasLimit: this._program.api.blockGasLimit.toBigInt(),
+      at: atBlock,
+    });
+    if (!reply.code.isSuccess)
+      throw new Error(
+        this._program.registry.createType("String", reply.payload).toString()
+      );
+    const result = this._program.registry.createType(
+      "(String, String, Option<[u8;32]>)",
+      reply.payload
+    );
+    return result[2].toJSON() as unknown as ActorId | null;
+  }
+
   public async getPointsLen(
+    job_id: number,
     originAddress?: string,
     value?: number | string | bigint,
     atBlock?: `0x${string}`
   ): Promise<number> {
     const payload = this._program.registry
-      .createType("(String, String)", ["Manager", "GetPointsLen"])
+      .createType("(String, String, u32)", ["Manager", "GetPointsLen", job_id])
       .toHex();
     const reply = await this._program.api.message.calculateReply({
       destination: this._program.programId!,
@@ -239,18 +1009,20 @@ export class Manager {
   }
 
   public async getResults(
-    start_index: number,
-    end_index: number,
+    job_id: number,
+    cursor: number,
+    limit: number,
     originAddress?: string,
     value?: number | string | bigint,
     atBlock?: `0x${string}`
-  ): Promise<Array<PointResult>> {
+  ): Promise<{ ok: ResultsPage } | { err: ManagerError }> {
     const payload = this._program.registry
-      .createType("(String, String, u32, u32)", [
+      .createType("(String, String, u32, u32, u32)", [
         "Manager",
         "GetResults",
-        start_index,
-        end_index,
+        job_id,
+        cursor,
+        limit,
       ])
       .toHex();
     const reply = await this._program.api.message.calculateReply({
@@ -266,19 +1038,76 @@ export class Manager {
         this._program.registry.createType("String", reply.payload).toString()
       );
     const result = this._program.registry.createType(
-      "(String, String, Vec<PointResult>)",
+      "(String, String, Result<ResultsPage, ManagerError>)",
+      reply.payload
+    );
+    return result[2].toJSON() as unknown as
+      | { ok: ResultsPage }
+      | { err: ManagerError };
+  }
+
+  public async getVerification(
+    originAddress?: string,
+    value?: number | string | bigint,
+    atBlock?: `0x${string}`
+  ): Promise<VerificationConfig> {
+    const payload = this._program.registry
+      .createType("(String, String)", ["Manager", "GetVerification"])
+      .toHex();
+    const reply = await this._program.api.message.calculateReply({
+      destination: this._program.programId!,
+      origin: originAddress ? decodeAddress(originAddress) : ZERO_ADDRESS,
+      payload,
+      value: value || 0,
+      gasLimit: this._program.api.blockGasLimit.toBigInt(),
+      at: atBlock,
+    });
+    if (!reply.code.isSuccess)
+      throw new Error(
+        this._program.registry.createType("String", reply.payload).toString()
+      );
+    const result = this._program.registry.createType(
+      "(String, String, VerificationConfig)",
+      reply.payload
+    );
+    return result[2].toJSON() as unknown as VerificationConfig;
+  }
+
+  public async owner(
+    originAddress?: string,
+    value?: number | string | bigint,
+    atBlock?: `0x${string}`
+  ): Promise<ActorId> {
+    const payload = this._program.registry
+      .createType("(String, String)", ["Manager", "Owner"])
+      .toHex();
+    const reply = await this._program.api.message.calculateReply({
+      destination: this._program.programId!,
+      origin: originAddress ? decodeAddress(originAddress) : ZERO_ADDRESS,
+      payload,
+      value: value || 0,
+      gasLimit: this._program.api.blockGasLimit.toBigInt(),
+      at: atBlock,
+    });
+    if (!reply.code.isSuccess)
+      throw new Error(
+        this._program.registry.createType("String", reply.payload).toString()
+      );
+    const result = this._program.registry.createType(
+      "(String, String, [u8;32])",
       reply.payload
     );
-    return result[2].toJSON() as unknown as Array<PointResult>;
+    return result[2].toJSON() as unknown as ActorId;
   }
 
   public async pointsSent(
+    job_id: number,
     originAddress?: string,
     value?: number | string | bigint,
     atBlock?: `0x${string}`
   ): Promise<number> {
     const payload = this._program.registry
-      .createType("(String, String)", ["Manager", "PointsSent"])
+      .createType("(String, String, u32)", ["Manager", "PointsSent", job_id])
       .toHex();
     const reply = await this._program.api.message.calculateReply({
       destination: this._program.programId!,
@@ -298,4 +1127,315 @@ export class Manager {
     );
     return result[2].toNumber() as unknown as number;
   }
+
+  public subscribeToJobCreatedEvent(
+    callback: (data: {
+      job_id: number;
+      creator: ActorId;
+    }) => void | Promise<void>
+  ): Promise<() => void> {
+    return this._program.api.gearEvents.subscribeToGearEvent(
+      "UserMessageSent",
+      ({ data: { message } }) => {
+        if (
+          !message.source.eq(this._program.programId) ||
+          !message.destination.eq(ZERO_ADDRESS)
+        ) {
+          return;
+        }
+
+        const payload = message.payload.toHex();
+        if (
+          getServiceNamePrefix(payload) === "Manager" &&
+          getFnNamePrefix(payload) === "JobCreated"
+        ) {
+          callback(
+            this._program.registry
+              .createType(
+                '(String, String, {"job_id":"u32","creator":"[u8;32]"})',
+                message.payload
+              )[2]
+              .toJSON() as unknown as { job_id: number; creator: ActorId }
+          );
+        }
+      }
+    );
+  }
+
+  public subscribeToPointsGeneratedEvent(
+    callback: (data: { job_id: number; total: number }) => void | Promise<void>
+  ): Promise<() => void> {
+    return this._program.api.gearEvents.subscribeToGearEvent(
+      "UserMessageSent",
+      ({ data: { message } }) => {
+        if (
+          !message.source.eq(this._program.programId) ||
+          !message.destination.eq(ZERO_ADDRESS)
+        ) {
+          return;
+        }
+
+        const payload = message.payload.toHex();
+        if (
+          getServiceNamePrefix(payload) === "Manager" &&
+          getFnNamePrefix(payload) === "PointsGenerated"
+        ) {
+          callback(
+            this._program.registry
+              .createType(
+                '(String, String, {"job_id":"u32","total":"u32"})',
+                message.payload
+              )[2]
+              .toJSON() as unknown as { job_id: number; total: number }
+          );
+        }
+      }
+    );
+  }
+
+  public subscribeToBatchDispatchedEvent(
+    callback: (data: {
+      job_id: number;
+      checker: ActorId;
+      count: number;
+    }) => void | Promise<void>
+  ): Promise<() => void> {
+    return this._program.api.gearEvents.subscribeToGearEvent(
+      "UserMessageSent",
+      ({ data: { message } }) => {
+        if (
+          !message.source.eq(this._program.programId) ||
+          !message.destination.eq(ZERO_ADDRESS)
+        ) {
+          return;
+        }
+
+        const payload = message.payload.toHex();
+        if (
+          getServiceNamePrefix(payload) === "Manager" &&
+          getFnNamePrefix(payload) === "BatchDispatched"
+        ) {
+          callback(
+            this._program.registry
+              .createType(
+                '(String, String, {' +
+                  '"job_id":"u32",' +
+                  '"checker":"[u8;32]",' +
+                  '"count":"u32"' +
+                  '})',
+                message.payload
+              )[2]
+              .toJSON() as unknown as {
+              job_id: number;
+              checker: ActorId;
+              count: number;
+            }
+          );
+        }
+      }
+    );
+  }
+
+  public subscribeToBatchCompletedEvent(
+    callback: (data: {
+      job_id: number;
+      checker: ActorId;
+      count: number;
+    }) => void | Promise<void>
+  ): Promise<() => void> {
+    return this._program.api.gearEvents.subscribeToGearEvent(
+      "UserMessageSent",
+      ({ data: { message } }) => {
+        if (
+          !message.source.eq(this._program.programId) ||
+          !message.destination.eq(ZERO_ADDRESS)
+        ) {
+          return;
+        }
+
+        const payload = message.payload.toHex();
+        if (
+          getServiceNamePrefix(payload) === "Manager" &&
+          getFnNamePrefix(payload) === "BatchCompleted"
+        ) {
+          callback(
+            this._program.registry
+              .createType(
+                '(String, String, {' +
+                  '"job_id":"u32",' +
+                  '"checker":"[u8;32]",' +
+                  '"count":"u32"' +
+                  '})',
+                message.payload
+              )[2]
+              .toJSON() as unknown as {
+              job_id: number;
+              checker: ActorId;
+              count: number;
+            }
+          );
+        }
+      }
+    );
+  }
+
+  public subscribeToRenderCompletedEvent(
+    callback: (data: {
+      job_id: number;
+      duration_blocks: number;
+    }) => void | Promise<void>
+  ): Promise<() => void> {
+    return this._program.api.gearEvents.subscribeToGearEvent(
+      "UserMessageSent",
+      ({ data: { message } }) => {
+        if (
+          !message.source.eq(this._program.programId) ||
+          !message.destination.eq(ZERO_ADDRESS)
+        ) {
+          return;
+        }
+
+        const payload = message.payload.toHex();
+        if (
+          getServiceNamePrefix(payload) === "Manager" &&
+          getFnNamePrefix(payload) === "RenderCompleted"
+        ) {
+          callback(
+            this._program.registry
+              .createType(
+                '(String, String, {"job_id":"u32","duration_blocks":"u32"})',
+                message.payload
+              )[2]
+              .toJSON() as unknown as {
+              job_id: number;
+              duration_blocks: number;
+            }
+          );
+        }
+      }
+    );
+  }
+
+  public subscribeToDisputesOpenedEvent(
+    callback: (data: {
+      job_id: number;
+      checker: ActorId;
+      verifier: ActorId;
+      count: number;
+    }) => void | Promise<void>
+  ): Promise<() => void> {
+    return this._program.api.gearEvents.subscribeToGearEvent(
+      "UserMessageSent",
+      ({ data: { message } }) => {
+        if (
+          !message.source.eq(this._program.programId) ||
+          !message.destination.eq(ZERO_ADDRESS)
+        ) {
+          return;
+        }
+
+        const payload = message.payload.toHex();
+        if (
+          getServiceNamePrefix(payload) === "Manager" &&
+          getFnNamePrefix(payload) === "DisputesOpened"
+        ) {
+          callback(
+            this._program.registry
+              .createType(
+                '(String, String, {' +
+                  '"job_id":"u32",' +
+                  '"checker":"[u8;32]",' +
+                  '"verifier":"[u8;32]",' +
+                  '"count":"u32"' +
+                  '})',
+                message.payload
+              )[2]
+              .toJSON() as unknown as {
+              job_id: number;
+              checker: ActorId;
+              verifier: ActorId;
+              count: number;
+            }
+          );
+        }
+      }
+    );
+  }
+
+  public subscribeToCheckersSpawnedEvent(
+    callback: (data: {
+      checkers: Array<ActorId>;
+      incompatible: Array<ActorId>;
+    }) => void | Promise<void>
+  ): Promise<() => void> {
+    return this._program.api.gearEvents.subscribeToGearEvent(
+      "UserMessageSent",
+      ({ data: { message } }) => {
+        if (
+          !message.source.eq(this._program.programId) ||
+          !message.destination.eq(ZERO_ADDRESS)
+        ) {
+          return;
+        }
+
+        const payload = message.payload.toHex();
+        if (
+          getServiceNamePrefix(payload) === "Manager" &&
+          getFnNamePrefix(payload) === "CheckersSpawned"
+        ) {
+          callback(
+            this._program.registry
+              .createType(
+                '(String, String, {' +
+                  '"checkers":"Vec<[u8;32]>",' +
+                  '"incompatible":"Vec<[u8;32]>"' +
+                  '})',
+                message.payload
+              )[2]
+              .toJSON() as unknown as {
+              checkers: Array<ActorId>;
+              incompatible: Array<ActorId>;
+            }
+          );
+        }
+      }
+    );
+  }
+
+  public subscribeToCheckerSlashedEvent(
+    callback: (data: {
+      checker: ActorId;
+      disputes_lost: number;
+    }) => void | Promise<void>
+  ): Promise<() => void> {
+    return this._program.api.gearEvents.subscribeToGearEvent(
+      "UserMessageSent",
+      ({ data: { message } }) => {
+        if (
+          !message.source.eq(this._program.programId) ||
+          !message.destination.eq(ZERO_ADDRESS)
+        ) {
+          return;
+        }
+
+        const payload = message.payload.toHex();
+        if (
+          getServiceNamePrefix(payload) === "Manager" &&
+          getFnNamePrefix(payload) === "CheckerSlashed"
+        ) {
+          callback(
+            this._program.registry
+              .createType(
+                '(String, String, {"checker":"[u8;32]","disputes_lost":"u32"})',
+                message.payload
+              )[2]
+              .toJSON() as unknown as {
+              checker: ActorId;
+              disputes_lost: number;
+            }
+          );
+        }
+      }
+    );
+  }
 }
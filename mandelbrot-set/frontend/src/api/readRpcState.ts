@@ -3,42 +3,118 @@ import { TypeRegistry } from "@polkadot/types";
 import { RegistryTypes } from "@polkadot/types/types";
 import { useQuery } from "@tanstack/react-query";
 import { useReadContract } from "wagmi";
-import { PointResult } from "./lib";
+import { JobInfo, ManagerError, PointResult, ResultsPage } from "./lib";
 import { CONTRACT_ADDRESS, GEAR_API_NODE } from "../consts";
 import { abi } from "../assets/abi";
 
-const RESPONSE_SIZE = 100000;
-
-export const readRpcState = async (
-  mirrorId?: HexString,
-  startIndex = 0,
-  endIndex = RESPONSE_SIZE
-) => {
-  if (!mirrorId) return [];
-  console.log("read state from startIndex:", startIndex);
-
-  const types: RegistryTypes = {
-    PointResult: {
-      c_re: "i128",
-      c_im: "i128",
-      iter: "u32",
-      checked: "bool",
+// `MAX_RESULTS_PAGE_SIZE` of the manager.
+const RESPONSE_SIZE = 20000;
+const REFETCH_INTERVAL = 3000;
+
+const types: RegistryTypes = {
+  FixedPoint: { num: "i64", scale: "u32" },
+  BigFixedPoint: { num: "Vec<u8>", scale: "u32" },
+  Fractal: {
+    _enum: {
+      Mandelbrot: "Null",
+      Julia: { c_re: "FixedPoint", c_im: "FixedPoint" },
+      Multibrot: { power: "u32" },
+      BurningShip: "Null",
     },
-  };
-
-  const registry = new TypeRegistry();
-  registry.setKnownTypes({ types });
-  registry.register(types);
+  },
+  DeepZoom: {
+    center_re: "BigFixedPoint",
+    center_im: "BigFixedPoint",
+    offset_scale: "u32",
+  },
+  JobParams: {
+    fractal: "Fractal",
+    width: "u32",
+    height: "u32",
+    x_min: "FixedPoint",
+    x_max: "FixedPoint",
+    y_min: "FixedPoint",
+    y_max: "FixedPoint",
+    max_iter: "u32",
+    smooth: "bool",
+    deep_zoom: "Option<DeepZoom>",
+    adaptive: "bool",
+  },
+  JobStatus: { _enum: ["Generating", "Checking", "Completed"] },
+  JobInfo: {
+    id: "u32",
+    creator: "[u8;32]",
+    params: "JobParams",
+    status: "JobStatus",
+    total_points: "u32",
+    points_generated: "u32",
+    points_sent: "u32",
+    points_checked: "u32",
+    points_filled: "u32",
+    budget: "u128",
+    reward_per_point: "u128",
+    created_at: "u32",
+    started_at: "Option<u32>",
+    completed_at: "Option<u32>",
+    results_version: "u32",
+  },
+  PointResult: {
+    index: "u32",
+    c_re: "FixedPoint",
+    c_im: "FixedPoint",
+    iter: "u32",
+    smooth: "Option<FixedPoint>",
+    checked: "bool",
+  },
+  ResultsPage: { results: "Vec<PointResult>", next_cursor: "Option<u32>" },
+  ManagerError: {
+    _enum: [
+      "NotOwner",
+      "NotAdmin",
+      "UnknownChecker",
+      "AlreadyAdmin",
+      "NotAnAdmin",
+      "CheckerNotFound",
+      "CheckerAlreadyRegistered",
+      "InvalidResults",
+      "JobNotFound",
+      "NotJobCreator",
+      "InvalidJobParams",
+      "NotPayee",
+      "NothingToWithdraw",
+      "CheckerCodeNotSet",
+      "IncompatibleChecker",
+      "TooManyJobs",
+      "TooManyPoints",
+      "CheckerCreationFailed",
+    ],
+  },
+};
 
-  const payload = registry
-    .createType("(String, String, u32, u32)", [
+const registry = new TypeRegistry();
+registry.setKnownTypes({ types });
+registry.register(types);
+
+// The manager is reached through its mirror, so a call is the SCALE encoded
+// `(service, method, args)` tuple passed to `sendMessage`, see `manager.idl`.
+export const encodePayload = (
+  method: string,
+  argTypes: string[] = [],
+  args: unknown[] = []
+) =>
+  registry
+    .createType(`(${["String", "String", ...argTypes].join(", ")})`, [
       "Manager",
-      "GetResults",
-      startIndex,
-      endIndex,
+      method,
+      ...args,
     ])
     .toHex();
 
+const calculateReply = async <T>(
+  mirrorId: HexString,
+  payload: HexString,
+  replyType: string
+) => {
   const params = {
     jsonrpc: "2.0",
     id: 1,
@@ -51,9 +127,6 @@ export const readRpcState = async (
     },
   };
 
-  const myHeaders = new Headers();
-  myHeaders.append("Content-Type", "application/json");
-
   const response = await fetch(GEAR_API_NODE, {
     method: "POST",
     headers: {
@@ -65,64 +138,95 @@ export const readRpcState = async (
   const json = await response.json();
 
   const result = registry.createType(
-    "(String, String, Vec<PointResult>)",
+    `(String, String, ${replyType})`,
     json.result.payload
   );
 
-  let data = result[2].toJSON() as unknown as Array<PointResult>;
+  return result[2].toJSON() as unknown as T;
+};
+
+export const readJobs = async (mirrorId?: HexString) => {
+  if (!mirrorId) return [];
+
+  return calculateReply<Array<JobInfo>>(
+    mirrorId,
+    encodePayload("GetJobs"),
+    "Vec<JobInfo>"
+  );
+};
+
+export const readRpcState = async (
+  mirrorId?: HexString,
+  jobId?: number,
+  cursor = 0
+): Promise<Array<PointResult>> => {
+  if (!mirrorId || jobId === undefined) return [];
+  console.log("read state from cursor:", cursor);
+
+  const page = await calculateReply<
+    { ok: ResultsPage } | { err: ManagerError }
+  >(
+    mirrorId,
+    encodePayload(
+      "GetResults",
+      ["u32", "u32", "u32"],
+      [jobId, cursor, RESPONSE_SIZE]
+    ),
+    "Result<ResultsPage, ManagerError>"
+  );
+
+  if ("err" in page) throw new Error(page.err);
 
-  if (data?.length === RESPONSE_SIZE) {
-    const newData = await readRpcState(
-      mirrorId,
-      startIndex + RESPONSE_SIZE,
-      endIndex + RESPONSE_SIZE
-    );
-    if (newData) {
-      data = [...data, ...newData];
-    }
-  }
+  const { results, next_cursor } = page.ok;
+  if (next_cursor === null) return results;
 
-  return data;
+  return [...results, ...(await readRpcState(mirrorId, jobId, next_cursor))];
 };
 
-export const useReadRpcState = () => {
+const useMirrorId = () => {
   const { data: mirrorId } = useReadContract({
     abi,
     address: CONTRACT_ADDRESS,
     functionName: "mirror",
   });
 
+  return mirrorId as HexString | undefined;
+};
+
+export const useReadJobs = () => {
+  const mirrorId = useMirrorId();
+
   const { data, isPending, refetch } = useQuery({
-    queryKey: ["readState", mirrorId],
-    queryFn: async () => await readRpcState(mirrorId as HexString),
+    queryKey: ["readJobs", mirrorId],
+    queryFn: async () => await readJobs(mirrorId),
+    refetchInterval: (query) =>
+      query.state.data?.some(({ status }) => status !== "Completed")
+        ? REFETCH_INTERVAL
+        : false,
   });
 
-  const retryWhileDataChanged = () =>
-    new Promise<void>((resolve) => {
-      const isEmptyPrevData = data?.length === 0;
-
-      const retry = async (atempt = 0) => {
-        const response = await refetch();
-        const isEmptyNextData = response.data?.length === 0;
-        const isDataChanged = isEmptyPrevData !== isEmptyNextData;
+  return {
+    jobs: data,
+    jobsPending: isPending,
+    refetchJobs: refetch,
+  };
+};
 
-        if (isDataChanged) {
-          console.log("resolved on atempt", atempt);
-          resolve();
-        } else {
-          setTimeout(() => {
-            retry(atempt + 1);
-          }, 1000);
-        }
-      };
+// Results of a job are read again until it completes, so the canvas fills in
+// while the checkers work.
+export const useReadRpcState = (job?: JobInfo) => {
+  const mirrorId = useMirrorId();
 
-      retry();
-    });
+  const { data, isPending, refetch } = useQuery({
+    queryKey: ["readState", mirrorId, job?.id],
+    queryFn: async () => await readRpcState(mirrorId, job?.id),
+    refetchInterval:
+      job && job.status !== "Completed" ? REFETCH_INTERVAL : false,
+  });
 
   return {
     rpcState: data,
     rpcStatePending: isPending,
     refetch,
-    retryWhileDataChanged,
   };
 };
@@ -4,10 +4,16 @@ import {
   useWatchContractEvent,
   useAccount,
 } from "wagmi";
+import { HexString } from "@gear-js/api";
 import { useState } from "react";
 import { abi } from "@/assets/abi";
 import { CONTRACT_ADDRESS } from "@/consts";
-import { useReadRpcState } from "@/api/readRpcState";
+import { JobParams } from "@/api/lib";
+import {
+  encodePayload,
+  useReadJobs,
+  useReadRpcState,
+} from "@/api/readRpcState";
 
 import { Button, Canvas, Input, Layout, StatePreview } from "@/components";
 import { HashLink } from "../ui/HashLink";
@@ -18,32 +24,35 @@ export const ComputationForm = () => {
   const [maxIter, setMaxIter] = useState(0);
   const [width, setWidth] = useState(0);
   const [height, setHeight] = useState(0);
+  const [selectedJobId, setSelectedJobId] = useState<number>();
 
   const { writeContract, isPending } = useWriteContract();
 
-  const { retryWhileDataChanged, rpcState, rpcStatePending } =
-    useReadRpcState();
+  const { jobs, jobsPending, refetchJobs } = useReadJobs();
+  // The newest job is shown until another one is selected.
+  const job =
+    jobs?.find(({ id }) => id === selectedJobId) ?? jobs?.[jobs.length - 1];
+  const { rpcState, rpcStatePending, refetch } = useReadRpcState(job);
+
+  const onReply = (logs: unknown) => {
+    console.log("Reply", logs);
+    Promise.all([refetchJobs(), refetch()]).then(() => setIsLoading(false));
+  };
 
   useWatchContractEvent({
     abi,
     address: CONTRACT_ADDRESS,
-    eventName: "ManagerGenerateAndStorePointsReply",
+    eventName: "OnReplyEvent",
     batch: false,
-    onLogs(logs) {
-      console.log("ManagerGenerateAndStorePointsReply", logs);
-      retryWhileDataChanged().then(() => setIsLoading(false));
-    },
+    onLogs: onReply,
   });
 
   useWatchContractEvent({
     abi,
     address: CONTRACT_ADDRESS,
-    eventName: "ManagerRestartReply",
+    eventName: "ErrorReply",
     batch: false,
-    onLogs(logs) {
-      console.log("ManagerRestartReply", logs);
-      retryWhileDataChanged().then(() => setIsLoading(false));
-    },
+    onLogs: onReply,
   });
 
   const onSuccess = () => {
@@ -58,14 +67,14 @@ export const ComputationForm = () => {
     setIsLoading(false);
   };
 
-  const onRestart = () => {
+  const sendMessage = (payload: HexString) => {
     setIsLoading(true);
     writeContract(
       {
         abi,
         address: CONTRACT_ADDRESS,
-        functionName: "fnManagerRestart",
-        args: [0],
+        functionName: "sendMessage",
+        args: [payload, 0n],
       },
       {
         onSuccess,
@@ -74,39 +83,50 @@ export const ComputationForm = () => {
     );
   };
 
+  const onCreateJob = () => {
+    const params: JobParams = {
+      fractal: { mandelbrot: null },
+      width,
+      height,
+      x_min: { num: -2, scale: 0 },
+      x_max: { num: 1, scale: 0 },
+      y_min: { num: -15, scale: 1 },
+      y_max: { num: 15, scale: 1 },
+      max_iter: maxIter,
+      smooth: false,
+      deep_zoom: null,
+      adaptive: false,
+    };
+    setSelectedJobId(undefined);
+    sendMessage(encodePayload("CreateJob", ["JobParams"], [params]));
+  };
+
   const onGenerateAndStorePoints = () => {
-    setIsLoading(true);
-    writeContract(
-      {
-        abi,
-        address: CONTRACT_ADDRESS,
-        functionName: "fnManagerGenerateAndStorePoints",
-        args: [
-          width,
-          height,
-          { num: -2, scale: 0 },
-          { num: 1, scale: 0 },
-          { num: -15, scale: 1 },
-          { num: 15, scale: 1 },
-          30000,
-          true,
-          true,
-          1000,
-          20,
-          0,
-        ],
-      },
-      {
-        onSuccess,
-        onError,
-      }
+    if (!job) return;
+    sendMessage(
+      encodePayload(
+        "GenerateAndStorePoints",
+        ["u32", "u32", "bool", "bool", "u32"],
+        [job.id, 30000, true, true, 20]
+      )
     );
   };
 
+  const onRestartJob = () => {
+    if (!job) return;
+    sendMessage(encodePayload("RestartJob", ["u32"], [job.id]));
+  };
+
+  const onRemoveJob = () => {
+    if (!job) return;
+    setSelectedJobId(undefined);
+    sendMessage(encodePayload("RemoveJob", ["u32"], [job.id]));
+  };
+
   const chainName = ethAccount.chain!.name;
   const hasState = rpcState && rpcState.length > 0;
-  const pending = rpcStatePending || isPending || isLoading;
-  const disabled = pending || hasState;
+  const isStarted = job && job.points_generated > 0;
+  const pending = jobsPending || rpcStatePending || isPending || isLoading;
 
   return (
     <Layout className="flex flex-col gap-5">
@@ -123,7 +143,7 @@ export const ComputationForm = () => {
         onChange={(event) => {
           setMaxIter(Number(event.currentTarget.value) || 0);
         }}
-        disabled={disabled}
+        disabled={pending}
       />
 
       <Input
@@ -132,7 +152,7 @@ export const ComputationForm = () => {
         onChange={(event) => {
           setWidth(Number(event.currentTarget.value) || 0);
         }}
-        disabled={disabled}
+        disabled={pending}
       />
 
       <Input
@@ -141,35 +161,81 @@ export const ComputationForm = () => {
         onChange={(event) => {
           setHeight(Number(event.currentTarget.value) || 0);
         }}
-        disabled={disabled}
+        disabled={pending}
       />
 
       <div style={{ display: "flex", gap: "12px" }}>
-        {hasState ? (
-          <Button
-            variant="outline"
-            onClick={onRestart}
-            isLoading={pending}
-            className="w-52"
-          >
-            Cleanup Contract state
-          </Button>
-        ) : (
+        <Button
+          variant="outline"
+          onClick={onCreateJob}
+          isLoading={pending}
+          className="w-50"
+        >
+          Create job
+        </Button>
+      </div>
+
+      {jobs && jobs.length > 0 && (
+        <select
+          value={job?.id}
+          onChange={(event) => {
+            setSelectedJobId(Number(event.currentTarget.value));
+          }}
+          disabled={pending}
+          className="w-full border border-muted-foreground bg-background px-4 py-3 text-xs"
+        >
+          {jobs.map(({ id, params, status }) => (
+            <option key={id} value={id}>
+              Job {id}: {params.width}x{params.height}, {status}
+            </option>
+          ))}
+        </select>
+      )}
+
+      {job && (
+        <div style={{ display: "flex", gap: "12px" }}>
+          {isStarted ? (
+            <Button
+              variant="outline"
+              onClick={onRestartJob}
+              isLoading={pending}
+              className="w-52"
+            >
+              Restart job
+            </Button>
+          ) : (
+            <Button
+              variant="outline"
+              onClick={onGenerateAndStorePoints}
+              isLoading={pending}
+              className="w-50"
+            >
+              Start calculation
+            </Button>
+          )}
           <Button
             variant="outline"
-            onClick={onGenerateAndStorePoints}
+            onClick={onRemoveJob}
             isLoading={pending}
             className="w-50"
           >
-            Start calculation
+            Remove job
           </Button>
-        )}
-      </div>
+        </div>
+      )}
 
-      {hasState && (
+      {job && hasState && (
         <>
-          <p>Contract state (length: {rpcState.length})</p>
-          <Canvas nodes={rpcState} />
+          <p>
+            Job {job.id} results (length: {rpcState.length} of{" "}
+            {job.total_points})
+          </p>
+          <Canvas
+            nodes={rpcState}
+            width={job.params.width}
+            height={job.params.height}
+            maxIter={job.params.max_iter}
+          />
           <StatePreview nodes={rpcState} />
         </>
       )}
@@ -0,0 +1,251 @@
+use crate::{
+    job::{JobId, JobParams},
+    Capabilities,
+};
+use sails_rs::{
+    collections::{HashMap, VecDeque},
+    prelude::*,
+};
+
+/// Blocks after which a batch without reply is taken back from its checker
+/// and sent again.
+pub const BATCH_TIMEOUT_BLOCKS: u32 = 100;
+
+#[derive(Debug, Default, Encode, Decode, TypeInfo, Clone, PartialEq, Eq)]
+pub struct CheckerStats {
+    pub batches_completed: u32,
+    pub points_processed: u64,
+    /// Replies that did not match the batch the checker was given, and
+    /// batches it did not answer in time.
+    pub failures: u32,
+    /// Batches sent to the checker and not answered yet.
+    pub pending_batches: u32,
+    /// Block of the last batch dispatched to or answered by the checker.
+    pub last_active_block: u32,
+    /// Disputes the checker lost, see `crate::verification`.
+    pub disputes_lost: u32,
+}
+
+/// What the results of a batch are used for.
+#[derive(Clone)]
+pub enum BatchKind {
+    /// Results of the job.
+    Compute,
+    /// A sample of the points `checker` answered with `results`, computed again.
+    Verify { checker: ActorId, results: Vec<u32> },
+    /// Contested points, the positions of their disputes by index.
+    Arbitrate { disputes: Vec<u32> },
+}
+
+/// Point indexes of a job sent to a checker.
+#[derive(Clone)]
+pub struct PendingBatch {
+    /// Sent with the points and echoed in the reply.
+    pub id: u64,
+    pub job_id: JobId,
+    pub indexes: Vec<u32>,
+    /// Set when the job is restarted or removed: the results of the batch are
+    /// dropped when they arrive.
+    pub stale: bool,
+    pub kind: BatchKind,
+    /// Block the batch was sent in.
+    pub dispatched_at: u32,
+}
+
+/// Registered checkers in dispatch order, with their statistics and the batches
+/// they are working on.
+///
+/// Every batch has an id that the checker sends back in its
+/// `ResultCalculated` reply. Replies are matched by that id rather than by
+/// order: a batch whose message runs out of gas or panics is never answered,
+/// and the batches after it must still be recognized. Such a batch is taken
+/// back once it is older than `BATCH_TIMEOUT_BLOCKS`.
+#[derive(Default)]
+pub struct CheckerRegistry {
+    checkers: Vec<ActorId>,
+    stats: HashMap<ActorId, CheckerStats>,
+    capabilities: HashMap<ActorId, Capabilities>,
+    pending: HashMap<ActorId, VecDeque<PendingBatch>>,
+}
+
+impl CheckerRegistry {
+    pub fn list(&self) -> &[ActorId] {
+        &self.checkers
+    }
+
+    pub fn contains(&self, checker: &ActorId) -> bool {
+        self.stats.contains_key(checker)
+    }
+
+    /// Whether `checker` can be registered: it is not the zero address and not registered yet.
+    pub fn can_add(&self, checker: &ActorId) -> bool {
+        *checker != ActorId::zero() && !self.contains(checker)
+    }
+
+    /// Registers a checker, ignoring the zero address and already registered ones.
+    pub fn add(&mut self, checker: ActorId, capabilities: Capabilities) -> bool {
+        if !self.can_add(&checker) {
+            return false;
+        }
+        self.checkers.push(checker);
+        self.stats.insert(checker, CheckerStats::default());
+        self.capabilities.insert(checker, capabilities);
+        true
+    }
+
+    /// Unregisters a checker and returns the batches it has not answered yet.
+    pub fn remove(&mut self, checker: &ActorId) -> Option<Vec<PendingBatch>> {
+        self.stats.remove(checker)?;
+        self.capabilities.remove(checker);
+        self.checkers.retain(|c| c != checker);
+        Some(
+            self.pending
+                .remove(checker)
+                .map(Vec::from)
+                .unwrap_or_default(),
+        )
+    }
+
+    /// Puts `new` in place of `old` in the dispatch order and returns the
+    /// batches `old` has not answered yet.
+    pub fn replace(
+        &mut self,
+        old: &ActorId,
+        new: ActorId,
+        capabilities: Capabilities,
+    ) -> Option<Vec<PendingBatch>> {
+        if !self.can_add(&new) {
+            return None;
+        }
+        let position = self.checkers.iter().position(|c| c == old)?;
+        self.checkers[position] = new;
+        self.stats.remove(old);
+        self.stats.insert(new, CheckerStats::default());
+        self.capabilities.remove(old);
+        self.capabilities.insert(new, capabilities);
+        Some(self.pending.remove(old).map(Vec::from).unwrap_or_default())
+    }
+
+    pub fn capabilities(&self, checker: &ActorId) -> Option<&Capabilities> {
+        self.capabilities.get(checker)
+    }
+
+    /// Whether the checker can compute the points of a job.
+    pub fn supports(&self, checker: &ActorId, params: &JobParams) -> bool {
+        self.capabilities.get(checker).is_some_and(|capabilities| {
+            capabilities.supports(&params.fractal, params.deep_zoom.is_some(), params.max_iter)
+        })
+    }
+
+    /// The batch size for the checker: `requested` up to its maximum, or its
+    /// maximum when `requested` is 0.
+    pub fn batch_size(&self, checker: &ActorId, requested: u32) -> u32 {
+        let max = self
+            .capabilities
+            .get(checker)
+            .map_or(0, |capabilities| capabilities.max_batch_size);
+        if requested == 0 {
+            max
+        } else {
+            requested.min(max)
+        }
+    }
+
+    /// The first checker after `after` in dispatch order that is not in
+    /// `excluded` and can compute the points of the job.
+    pub fn next_other(
+        &self,
+        after: &ActorId,
+        excluded: &[ActorId],
+        params: &JobParams,
+    ) -> Option<ActorId> {
+        let start = self
+            .checkers
+            .iter()
+            .position(|c| c == after)
+            .map_or(0, |position| position + 1);
+        self.checkers[start..]
+            .iter()
+            .chain(&self.checkers[..start])
+            .find(|checker| !excluded.contains(checker) && self.supports(checker, params))
+            .copied()
+    }
+
+    pub fn stats(&self, checker: &ActorId) -> Option<CheckerStats> {
+        let mut stats = self.stats.get(checker)?.clone();
+        stats.pending_batches = self.pending.get(checker).map_or(0, |p| p.len() as u32);
+        Some(stats)
+    }
+
+    pub fn all_stats(&self) -> Vec<(ActorId, CheckerStats)> {
+        self.checkers
+            .iter()
+            .filter_map(|checker| Some((*checker, self.stats(checker)?)))
+            .collect()
+    }
+
+    pub fn dispatched(&mut self, checker: ActorId, batch: PendingBatch, block: u32) {
+        if let Some(stats) = self.stats.get_mut(&checker) {
+            stats.last_active_block = block;
+        }
+        self.pending.entry(checker).or_default().push_back(batch);
+    }
+
+    /// Takes the batch of `checker` with the given id, the one its reply answers.
+    pub fn take_batch(&mut self, checker: &ActorId, id: u64, block: u32) -> Option<PendingBatch> {
+        if let Some(stats) = self.stats.get_mut(checker) {
+            stats.last_active_block = block;
+        }
+        let pending = self.pending.get_mut(checker)?;
+        let position = pending.iter().position(|batch| batch.id == id)?;
+        pending.remove(position)
+    }
+
+    /// Takes the batches dispatched `BATCH_TIMEOUT_BLOCKS` or more before
+    /// `block` and counts them as failures of their checkers.
+    pub fn take_expired(&mut self, block: u32) -> Vec<PendingBatch> {
+        let mut expired = Vec::new();
+        for (checker, pending) in self.pending.iter_mut() {
+            while let Some(batch) = pending.front() {
+                if block.saturating_sub(batch.dispatched_at) < BATCH_TIMEOUT_BLOCKS {
+                    break;
+                }
+                expired.extend(pending.pop_front());
+                if let Some(stats) = self.stats.get_mut(checker) {
+                    stats.failures += 1;
+                }
+            }
+        }
+        expired
+    }
+
+    pub fn record_success(&mut self, checker: &ActorId, points: u32) {
+        if let Some(stats) = self.stats.get_mut(checker) {
+            stats.batches_completed += 1;
+            stats.points_processed += points as u64;
+        }
+    }
+
+    pub fn record_failure(&mut self, checker: &ActorId) {
+        if let Some(stats) = self.stats.get_mut(checker) {
+            stats.failures += 1;
+        }
+    }
+
+    /// Flags the checker and returns the number of disputes it lost.
+    pub fn record_lost_dispute(&mut self, checker: &ActorId) -> u32 {
+        self.stats.get_mut(checker).map_or(0, |stats| {
+            stats.disputes_lost += 1;
+            stats.disputes_lost
+        })
+    }
+
+    /// Marks the in-flight batches of a job as stale so that their late results are ignored.
+    pub fn mark_pending_stale(&mut self, job_id: JobId) {
+        self.pending
+            .values_mut()
+            .flat_map(|batches| batches.iter_mut())
+            .filter(|batch| batch.job_id == job_id)
+            .for_each(|batch| batch.stale = true);
+    }
+}
@@ -0,0 +1,546 @@
+use crate::{
+    refinement::{Refinement, Tile},
+    BigFixedPoint, FixedPoint, Fractal, Point, PointResult, ResultsPage,
+};
+use mandelbrot_common::{
+    kernel::SMOOTH_SCALE,
+    perturbation::{self, ReferenceOrbit},
+    Viewport,
+};
+use sails_rs::prelude::*;
+
+pub type JobId = u32;
+
+/// Highest number of decimal digits of the center of a deep-zoom job and of
+/// its offsets. It bounds the cost of computing the reference orbit.
+pub const MAX_DEEP_ZOOM_SCALE: u32 = 200;
+
+/// Highest `max_iter` of a job, the limit of the reference checker.
+pub const MAX_ITER: u32 = 100_000;
+
+/// Highest `max_iter` of a deep-zoom job. The reference orbit goes out with
+/// every batch at 24 bytes per iteration, so this keeps it near 240 KB.
+pub const MAX_DEEP_ZOOM_ITER: u32 = 10_000;
+
+/// Most points of a job. Any account can create jobs, and the results of
+/// every point are kept in the Manager state.
+pub const MAX_JOB_POINTS: u32 = 1_000_000;
+
+/// Most jobs an account can have in the Manager at once.
+pub const MAX_JOBS_PER_CREATOR: u32 = 4;
+
+/// Most points of all the jobs in the Manager together, so that the state and
+/// the generation messages the Manager pays for stay bounded whatever the
+/// number of accounts. Jobs count until they are removed.
+pub const MAX_LIVE_POINTS: u32 = 16 * MAX_JOB_POINTS;
+
+/// Renders a Mandelbrot job by perturbation around a center of any precision,
+/// see `mandelbrot_common::perturbation`.
+///
+/// The bounds of such a job are offsets from the center in units of
+/// `10^-offset_scale`: `x_min = -15, scale 1` with `offset_scale = 40` is
+/// `center_re - 1.5e-40`. The coordinates of the points are offsets too.
+#[derive(Encode, Decode, TypeInfo, Clone)]
+pub struct DeepZoom {
+    pub center_re: BigFixedPoint,
+    pub center_im: BigFixedPoint,
+    pub offset_scale: u32,
+}
+
+impl DeepZoom {
+    fn is_valid(&self) -> bool {
+        self.center_re.scale <= MAX_DEEP_ZOOM_SCALE
+            && self.center_im.scale <= MAX_DEEP_ZOOM_SCALE
+            && self.offset_scale <= MAX_DEEP_ZOOM_SCALE
+            && self.center_re.is_within(2)
+            && self.center_im.is_within(2)
+    }
+}
+
+/// The image a job renders: the fractal, resolution, bounds on the complex
+/// plane, the iteration limit passed to the checkers, whether they also
+/// compute smooth iteration counts and, for deep zooms, the reference point.
+///
+/// With `adaptive`, only the pixels near the boundaries between iteration
+/// counts are sent to the checkers, see `crate::refinement`.
+#[derive(Encode, Decode, TypeInfo, Clone)]
+pub struct JobParams {
+    pub fractal: Fractal,
+    pub width: u32,
+    pub height: u32,
+    pub x_min: FixedPoint,
+    pub x_max: FixedPoint,
+    pub y_min: FixedPoint,
+    pub y_max: FixedPoint,
+    pub max_iter: u32,
+    pub smooth: bool,
+    pub deep_zoom: Option<DeepZoom>,
+    pub adaptive: bool,
+}
+
+impl JobParams {
+    pub fn is_valid(&self) -> bool {
+        let (Some(x_min), Some(x_max), Some(y_min), Some(y_max)) = (
+            self.x_min.try_to_decimal(),
+            self.x_max.try_to_decimal(),
+            self.y_min.try_to_decimal(),
+            self.y_max.try_to_decimal(),
+        ) else {
+            return false;
+        };
+        let deep_zoom_is_valid = match &self.deep_zoom {
+            Some(deep_zoom) => {
+                self.fractal == Fractal::Mandelbrot
+                    && deep_zoom.is_valid()
+                    && self.max_iter <= MAX_DEEP_ZOOM_ITER
+            }
+            None => true,
+        };
+        self.fractal.is_valid()
+            && deep_zoom_is_valid
+            && self.width > 0
+            && self.height > 0
+            && self
+                .width
+                .checked_mul(self.height)
+                .is_some_and(|points| points <= MAX_JOB_POINTS)
+            && self.max_iter > 0
+            && self.max_iter <= MAX_ITER
+            && x_min < x_max
+            && y_min < y_max
+    }
+
+    pub fn viewport(&self) -> Viewport {
+        Viewport::new(
+            self.width,
+            self.height,
+            &self.x_min,
+            &self.x_max,
+            &self.y_min,
+            &self.y_max,
+        )
+    }
+
+    pub fn total_points(&self) -> u32 {
+        self.width * self.height
+    }
+}
+
+#[derive(Encode, Decode, TypeInfo, Clone, Debug, PartialEq, Eq)]
+pub enum JobStatus {
+    /// Points are being generated.
+    Generating,
+    /// All points are generated and are being sent to the checkers.
+    Checking,
+    /// Every point has been checked.
+    Completed,
+}
+
+#[derive(Encode, Decode, TypeInfo, Clone)]
+pub struct JobInfo {
+    pub id: JobId,
+    pub creator: ActorId,
+    pub params: JobParams,
+    pub status: JobStatus,
+    pub total_points: u32,
+    pub points_generated: u32,
+    pub points_sent: u32,
+    /// Points computed by a checker or, in adaptive jobs, filled in.
+    pub points_checked: u32,
+    /// Points of an adaptive job filled in without being sent.
+    pub points_filled: u32,
+    /// Value left to pay the checkers, see `crate::rewards`.
+    pub budget: u128,
+    pub reward_per_point: u128,
+    pub created_at: u32,
+    /// Block of the first batch sent to a checker.
+    pub started_at: Option<u32>,
+    pub completed_at: Option<u32>,
+    /// Changes whenever checked results change: on restart, and when a lost
+    /// dispute corrects a result. Clients that keep checked results drop them
+    /// when it changes.
+    pub results_version: u32,
+}
+
+pub struct RenderJob {
+    pub creator: ActorId,
+    pub params: JobParams,
+    viewport: Viewport,
+    /// Orbit of the center of a deep-zoom job, sent with every batch.
+    pub reference: Option<ReferenceOrbit>,
+    /// Iteration count of every generated point, by index.
+    iters: Vec<u32>,
+    /// Smooth iteration counts, by index, as numerators at `SMOOTH_SCALE`.
+    /// Empty unless `params.smooth` is set.
+    smooth: Vec<i64>,
+    /// One bit per generated point, set once its result is received or filled in.
+    checked: Vec<u64>,
+    points_sent: u32,
+    points_checked: u32,
+    points_filled: u32,
+    /// Size of the last batch taken from the job.
+    last_batch_size: u32,
+    budget: u128,
+    reward_per_point: u128,
+    /// Tiles and pixels to send of an adaptive job, from the moment all its
+    /// points are generated.
+    refinement: Option<Refinement>,
+    /// Indexes taken back from removed or failing checkers, sent again before new points.
+    requeued: Vec<u32>,
+    created_at: u32,
+    started_at: Option<u32>,
+    completed_at: Option<u32>,
+    results_version: u32,
+}
+
+impl RenderJob {
+    /// Creates a job funded with `funds`, paid out evenly over its points.
+    pub fn new(creator: ActorId, params: JobParams, funds: u128, created_at: u32) -> Self {
+        let reference = params.deep_zoom.as_ref().map(|deep_zoom| {
+            perturbation::reference_orbit(
+                &deep_zoom.center_re,
+                &deep_zoom.center_im,
+                deep_zoom.offset_scale,
+                params.max_iter,
+            )
+        });
+        let reward_per_point = funds / params.total_points() as u128;
+        Self {
+            creator,
+            viewport: params.viewport(),
+            budget: funds,
+            reward_per_point,
+            reference,
+            params,
+            iters: Vec::new(),
+            smooth: Vec::new(),
+            checked: Vec::new(),
+            points_sent: 0,
+            points_checked: 0,
+            points_filled: 0,
+            last_batch_size: 0,
+            refinement: None,
+            requeued: Vec::new(),
+            created_at,
+            started_at: None,
+            completed_at: None,
+            results_version: 0,
+        }
+    }
+
+    pub fn points_generated(&self) -> u32 {
+        self.iters.len() as u32
+    }
+
+    pub fn points_sent(&self) -> u32 {
+        self.points_sent
+    }
+
+    pub fn is_generated(&self) -> bool {
+        self.points_generated() >= self.params.total_points()
+    }
+
+    pub fn is_completed(&self) -> bool {
+        self.points_checked >= self.params.total_points()
+    }
+
+    /// Makes up to `count` more points available for checking and returns how
+    /// many were added. Coordinates are derived from the index when the points
+    /// are sent, so only the result slots are allocated here.
+    pub fn generate(&mut self, count: u32) -> u32 {
+        let generated = self.points_generated();
+        let count = count.min(self.params.total_points().saturating_sub(generated));
+        let new_len = (generated + count) as usize;
+        self.iters.resize(new_len, 0);
+        if self.params.smooth {
+            self.smooth.resize(new_len, 0);
+        }
+        self.checked.resize(new_len.div_ceil(64), 0);
+        if self.params.adaptive && self.is_generated() {
+            self.refinement = Some(Refinement::new(self.params.width, self.params.height));
+        }
+        count
+    }
+
+    /// Coordinates of the point with the given index, see `mandelbrot_common::grid`.
+    pub fn coordinates(&self, index: u32) -> (FixedPoint, FixedPoint) {
+        self.viewport.coordinates(index)
+    }
+
+    fn is_checked(&self, index: u32) -> bool {
+        is_set(&self.checked, index)
+    }
+
+    fn set_checked(&mut self, index: u32) {
+        self.checked[index as usize / 64] |= 1 << (index % 64);
+    }
+
+    pub fn has_points_to_send(&self) -> bool {
+        !self.requeued.is_empty()
+            || match &self.refinement {
+                Some(refinement) => refinement.has_points_to_send(),
+                None => !self.params.adaptive && self.points_sent < self.points_generated(),
+            }
+    }
+
+    /// Takes the indexes of the next batch: requeued indexes first, then new points.
+    pub fn next_batch(&mut self, batch_size: u32, block: u32) -> Vec<u32> {
+        self.started_at.get_or_insert(block);
+        self.last_batch_size = batch_size;
+
+        let take = (batch_size as usize).min(self.requeued.len());
+        let mut indexes: Vec<u32> = self.requeued.drain(..take).collect();
+
+        let remaining = batch_size - indexes.len() as u32;
+        if let Some(refinement) = &mut self.refinement {
+            let checked = &self.checked;
+            let points = refinement.next_points(remaining as usize, |index| is_set(checked, index));
+            self.points_sent += points.len() as u32;
+            indexes.extend(points);
+        } else if !self.params.adaptive {
+            let start = self.points_sent;
+            let len = remaining.min(self.points_generated().saturating_sub(start));
+            indexes.extend(start..start + len);
+            self.points_sent += len;
+        }
+
+        indexes
+    }
+
+    /// Takes the reward of `points` from the budget, or what is left of it.
+    pub fn pay(&mut self, points: u32) -> u128 {
+        let reward = (self.reward_per_point * points as u128).min(self.budget);
+        self.budget -= reward;
+        reward
+    }
+
+    pub fn refund(&mut self, amount: u128) {
+        self.budget += amount;
+    }
+
+    pub fn reward_per_point(&self) -> u128 {
+        self.reward_per_point
+    }
+
+    /// Empties the budget and returns it.
+    pub fn take_budget(&mut self) -> u128 {
+        core::mem::take(&mut self.budget)
+    }
+
+    pub fn last_batch_size(&self) -> u32 {
+        self.last_batch_size
+    }
+
+    pub fn requeue(&mut self, indexes: Vec<u32>) {
+        self.requeued.extend(indexes);
+    }
+
+    pub fn points(&self, indexes: &[u32]) -> Vec<Point> {
+        indexes
+            .iter()
+            .filter(|&&index| index < self.points_generated())
+            .map(|&index| {
+                let (c_re, c_im) = self.coordinates(index);
+                Point { index, c_re, c_im }
+            })
+            .collect()
+    }
+
+    /// Whether a checker reply carries the smooth iteration counts the job
+    /// expects, one per point at `SMOOTH_SCALE`.
+    pub fn is_valid_smooth(&self, points: usize, smooth: &[FixedPoint]) -> bool {
+        if self.params.smooth {
+            smooth.len() == points && smooth.iter().all(|smooth| smooth.scale == SMOOTH_SCALE)
+        } else {
+            smooth.is_empty()
+        }
+    }
+
+    /// Stores the results and returns the number of blocks the render took if
+    /// they were the last missing ones.
+    pub fn record_results(
+        &mut self,
+        indexes: Vec<u32>,
+        results: Vec<u32>,
+        smooth: Vec<FixedPoint>,
+        block: u32,
+    ) -> Option<u32> {
+        let mut smooth = smooth.into_iter();
+        for (index, result) in indexes.into_iter().zip(results) {
+            let smooth_result = smooth.next();
+            if index >= self.points_generated() {
+                continue;
+            }
+            if !self.is_checked(index) {
+                self.set_checked(index);
+                self.points_checked += 1;
+            }
+            self.iters[index as usize] = result;
+            if let Some(smooth_result) = smooth_result {
+                self.smooth[index as usize] = smooth_result.num;
+            }
+        }
+        self.refine();
+
+        if self.completed_at.is_some() || !self.is_completed() {
+            return None;
+        }
+        self.completed_at = Some(block);
+        Some(block - self.started_at.unwrap_or(self.created_at))
+    }
+
+    /// Replaces the result of a point that lost a dispute, with its smooth
+    /// count in smooth jobs.
+    pub fn correct_result(&mut self, index: u32, iter: u32, smooth: Option<FixedPoint>) {
+        if let Some(result) = self.iters.get_mut(index as usize) {
+            *result = iter;
+        }
+        if let (Some(result), Some(smooth)) = (self.smooth.get_mut(index as usize), smooth) {
+            *result = smooth.num;
+        }
+        self.results_version = self.results_version.wrapping_add(1);
+    }
+
+    /// Fills the uniform tiles of an adaptive job until no tile can be settled
+    /// with the known points.
+    fn refine(&mut self) {
+        loop {
+            let Some(refinement) = &mut self.refinement else {
+                return;
+            };
+            let (checked, iters) = (&self.checked, &self.iters);
+            let uniform = refinement.refine(
+                |index| is_set(checked, index),
+                |index| iters[index as usize],
+            );
+            if uniform.is_empty() {
+                return;
+            }
+            for tile in uniform {
+                self.fill(&tile);
+            }
+        }
+    }
+
+    /// Gives the unknown pixels of a uniform tile the iteration count of its
+    /// corners, and smooth counts interpolated between theirs.
+    fn fill(&mut self, tile: &Tile) {
+        let width = self.params.width;
+        let iter = self.iters[(tile.y0 * width + tile.x0) as usize];
+        for y in tile.y0..=tile.y1 {
+            for x in tile.x0..=tile.x1 {
+                let index = y * width + x;
+                if self.is_checked(index) {
+                    continue;
+                }
+                self.iters[index as usize] = iter;
+                if self.params.smooth {
+                    self.smooth[index as usize] = self.interpolate_smooth(tile, x, y);
+                }
+                self.set_checked(index);
+                self.points_checked += 1;
+                self.points_filled += 1;
+            }
+        }
+    }
+
+    /// Bilinear interpolation of the smooth counts of the corners of the tile.
+    fn interpolate_smooth(&self, tile: &Tile, x: u32, y: u32) -> i64 {
+        let width = self.params.width;
+        let corner = |x: u32, y: u32| self.smooth[(y * width + x) as usize];
+        let corners = [
+            corner(tile.x0, tile.y0),
+            corner(tile.x1, tile.y0),
+            corner(tile.x0, tile.y1),
+            corner(tile.x1, tile.y1),
+        ];
+        let (span_x, span_y) = ((tile.x1 - tile.x0).max(1), (tile.y1 - tile.y0).max(1));
+        let (right, bottom) = (x - tile.x0, y - tile.y0);
+        let weights = [
+            (span_x - right) * (span_y - bottom),
+            right * (span_y - bottom),
+            (span_x - right) * bottom,
+            right * bottom,
+        ];
+        let sum: i128 = corners
+            .iter()
+            .zip(weights)
+            .map(|(&corner, weight)| corner as i128 * weight as i128)
+            .sum();
+        (sum / (span_x as i128 * span_y as i128)) as i64
+    }
+
+    /// Throws away the generated points and results, keeping the parameters.
+    /// The rewards already paid are gone, so the rest of the budget is split
+    /// over the points again.
+    pub fn reset(&mut self) {
+        self.reward_per_point = self.budget / self.params.total_points() as u128;
+        self.iters.clear();
+        self.smooth.clear();
+        self.checked.clear();
+        self.points_sent = 0;
+        self.points_checked = 0;
+        self.points_filled = 0;
+        self.refinement = None;
+        self.requeued.clear();
+        self.started_at = None;
+        self.completed_at = None;
+        self.results_version = self.results_version.wrapping_add(1);
+    }
+
+    pub fn results(&self, cursor: u32, limit: u32) -> ResultsPage {
+        let generated = self.points_generated();
+        let end = cursor.saturating_add(limit).min(generated);
+        let results = (cursor..end)
+            .map(|index| {
+                let (c_re, c_im) = self.coordinates(index);
+                PointResult {
+                    index,
+                    c_re,
+                    c_im,
+                    iter: self.iters[index as usize],
+                    smooth: self.smooth.get(index as usize).map(|&num| FixedPoint {
+                        num,
+                        scale: SMOOTH_SCALE,
+                    }),
+                    checked: self.is_checked(index),
+                }
+            })
+            .collect();
+        ResultsPage {
+            results,
+            next_cursor: (end < generated).then_some(end),
+        }
+    }
+
+    pub fn info(&self, id: JobId) -> JobInfo {
+        let status = if self.is_completed() {
+            JobStatus::Completed
+        } else if self.is_generated() {
+            JobStatus::Checking
+        } else {
+            JobStatus::Generating
+        };
+        JobInfo {
+            id,
+            creator: self.creator,
+            params: self.params.clone(),
+            status,
+            total_points: self.params.total_points(),
+            points_generated: self.points_generated(),
+            points_sent: self.points_sent,
+            points_checked: self.points_checked,
+            points_filled: self.points_filled,
+            budget: self.budget,
+            reward_per_point: self.reward_per_point,
+            created_at: self.created_at,
+            started_at: self.started_at,
+            completed_at: self.completed_at,
+            results_version: self.results_version,
+        }
+    }
+}
+
+fn is_set(bits: &[u64], index: u32) -> bool {
+    bits[index as usize / 64] & (1 << (index % 64)) != 0
+}
@@ -1,29 +1,140 @@
 #![no_std]
 
-use rust_decimal::Decimal;
 use sails_rs::{
-    collections::HashMap,
-    gstd::{exec, msg},
+    collections::BTreeMap,
+    gstd::{exec, msg, prog::ProgramGenerator},
     prelude::*,
 };
+mod checkers;
+mod job;
+mod refinement;
+mod rewards;
+mod verification;
+use checkers::{BatchKind, CheckerRegistry, PendingBatch};
+pub use checkers::{CheckerStats, BATCH_TIMEOUT_BLOCKS};
+use job::RenderJob;
+pub use job::{
+    DeepZoom, JobId, JobInfo, JobParams, JobStatus, MAX_DEEP_ZOOM_ITER, MAX_ITER,
+    MAX_JOBS_PER_CREATOR, MAX_JOB_POINTS, MAX_LIVE_POINTS,
+};
+pub use mandelbrot_common::{BigFixedPoint, Capabilities, FixedPoint, Fractal};
+use rewards::Rewards;
+pub use verification::{Dispute, DisputeStatus, VerificationConfig};
 static mut STATE: Option<ManagerState> = None;
 #[derive(Default)]
 struct ManagerState {
-    checkers: Vec<ActorId>,
-    point_results: HashMap<u32, (FixedPoint, FixedPoint, u32, bool)>,
-    points_sent: u32,
+    owner: ActorId,
+    admins: Vec<ActorId>,
+    checkers: CheckerRegistry,
+    /// Code of the checkers the manager deploys itself.
+    checker_code: Option<CodeId>,
+    /// Checkers the manager deployed, in deployment order. Scaling only adds
+    /// and removes these, never the checkers registered by hand.
+    spawned: Vec<ActorId>,
+    jobs: BTreeMap<JobId, RenderJob>,
+    /// Number of jobs of every creator that has some.
+    jobs_per_creator: BTreeMap<ActorId, u32>,
+    /// Points of all the jobs, generated or not.
+    live_points: u32,
+    next_job_id: JobId,
+    next_batch_id: u64,
+    /// The job that received the last batch, so that batches are spread over
+    /// all active jobs in turn.
+    last_scheduled_job: Option<JobId>,
+    verification: VerificationConfig,
+    disputes: Vec<Dispute>,
+    rewards: Rewards,
 }
 
 impl ManagerState {
-    pub fn new() -> Self {
+    pub fn new(owner: ActorId) -> Self {
         Self {
-            checkers: Vec::new(),
-            point_results: HashMap::with_capacity(400_000),
-            points_sent: 0,
+            owner,
+            ..Default::default()
+        }
+    }
+
+    /// The owner, the admins and the manager itself (for the continuation
+    /// messages it sends to its own address) are allowed to run admin methods.
+    fn is_admin(&self, actor: &ActorId) -> bool {
+        *actor == self.owner || *actor == exec::program_id() || self.admins.contains(actor)
+    }
+
+    fn has_points_to_send(&self) -> bool {
+        self.jobs.values().any(|job| job.has_points_to_send())
+    }
+
+    /// Picks the next job with points to send that the checker supports,
+    /// going round the jobs in id order.
+    fn next_scheduled_job(&mut self, checker: &ActorId) -> Option<JobId> {
+        let after = self.last_scheduled_job.map_or(0, |id| id.saturating_add(1));
+        let job_id = self
+            .jobs
+            .range(after..)
+            .chain(self.jobs.range(..after))
+            .find(|(_, job)| {
+                job.has_points_to_send() && self.checkers.supports(checker, &job.params)
+            })
+            .map(|(id, _)| *id)?;
+        self.last_scheduled_job = Some(job_id);
+        Some(job_id)
+    }
+
+    /// Gives the indexes of unanswered batches back to their jobs. Unanswered
+    /// verifications are dropped.
+    fn requeue(&mut self, batches: Vec<PendingBatch>) {
+        for batch in batches
+            .into_iter()
+            .filter(|batch| !batch.stale && matches!(batch.kind, BatchKind::Compute))
+        {
+            if let Some(job) = self.jobs.get_mut(&batch.job_id) {
+                job.requeue(batch.indexes);
+            }
         }
     }
 }
 
+#[derive(Debug, Encode, Decode, TypeInfo, Clone, PartialEq, Eq)]
+pub enum ManagerError {
+    /// The method can only be called by the owner.
+    NotOwner,
+    /// The method can only be called by the owner or an admin.
+    NotAdmin,
+    /// The results were sent by a program that is not a registered checker.
+    UnknownChecker,
+    /// The admin is already in the admin list.
+    AlreadyAdmin,
+    /// The admin is not in the admin list.
+    NotAnAdmin,
+    /// The checker is not registered.
+    CheckerNotFound,
+    /// The checker is the zero address or is already registered.
+    CheckerAlreadyRegistered,
+    /// The results do not match the batch that was sent to the checker.
+    InvalidResults,
+    /// There is no job with the given id.
+    JobNotFound,
+    /// The method can only be called by the job creator or an admin.
+    NotJobCreator,
+    /// The resolution, bounds or iteration limit of the job are invalid.
+    InvalidJobParams,
+    /// The method can only be called by the payee of the checker.
+    NotPayee,
+    /// The checker has no reward to withdraw.
+    NothingToWithdraw,
+    /// No checker code is set to deploy checkers from.
+    CheckerCodeNotSet,
+    /// The checker did not answer the capabilities query, or speaks another
+    /// protocol version, or does not compute the Mandelbrot set.
+    IncompatibleChecker,
+    /// The creator already has `MAX_JOBS_PER_CREATOR` jobs.
+    TooManyJobs,
+    /// The job would take the points of all jobs over `MAX_LIVE_POINTS`.
+    TooManyPoints,
+    /// A checker program could not be created from the checker code.
+    CheckerCreationFailed,
+}
+
 #[derive(Encode, Decode, TypeInfo, Clone)]
 pub struct Point {
     pub index: u32,
@@ -31,32 +142,84 @@ pub struct Point {
     pub c_im: FixedPoint,
 }
 
+/// A computed point. The coordinates of a deep-zoom job are offsets from its
+/// center, see `DeepZoom`.
 #[derive(Encode, Decode, TypeInfo, Clone)]
 pub struct PointResult {
+    pub index: u32,
     pub c_re: FixedPoint,
     pub c_im: FixedPoint,
     pub iter: u32,
+    /// Smooth iteration count, for jobs created with `smooth`.
+    pub smooth: Option<FixedPoint>,
     pub checked: bool,
 }
 
+/// Results of consecutive points, ordered by index.
 #[derive(Encode, Decode, TypeInfo, Clone)]
-pub struct FixedPoint {
-    pub num: i64,
-    pub scale: u32,
+pub struct ResultsPage {
+    pub results: Vec<PointResult>,
+    /// Index to pass as `cursor` to get the next page, `None` after the last generated point.
+    pub next_cursor: Option<u32>,
 }
 
-impl FixedPoint {
-    pub fn from_decimal(decimal: Decimal) -> Self {
-        let scale = decimal.scale();
-        let num = decimal.mantissa() as i64;
-        Self { num, scale }
-    }
+#[derive(Encode, Decode, TypeInfo, Clone)]
+pub enum Event {
+    JobCreated {
+        job_id: JobId,
+        creator: ActorId,
+    },
+    /// `total` is the number of points of the job generated so far.
+    PointsGenerated {
+        job_id: JobId,
+        total: u32,
+    },
+    BatchDispatched {
+        job_id: JobId,
+        checker: ActorId,
+        count: u32,
+    },
+    BatchCompleted {
+        job_id: JobId,
+        checker: ActorId,
+        count: u32,
+    },
+    /// Every point of the job is checked. `duration_blocks` is counted from the
+    /// first dispatched batch.
+    RenderCompleted {
+        job_id: JobId,
+        duration_blocks: u32,
+    },
+    /// `verifier` disagreed with `checker` on `count` points of a sample.
+    DisputesOpened {
+        job_id: JobId,
+        checker: ActorId,
+        verifier: ActorId,
+        count: u32,
+    },
+    /// Checkers deployed by the manager and added to the pool. `incompatible`
+    /// are the deployed programs that failed the capabilities query and were
+    /// not registered.
+    CheckersSpawned {
+        checkers: Vec<ActorId>,
+        incompatible: Vec<ActorId>,
+    },
+    /// The checker lost `VerificationConfig::max_disputes` disputes and was
+    /// removed from the pool.
+    CheckerSlashed {
+        checker: ActorId,
+        disputes_lost: u32,
+    },
 }
+
+/// Largest number of results returned by one `get_results` call.
+pub const MAX_RESULTS_PAGE_SIZE: u32 = 20_000;
+
 struct ManagerService(());
 
 impl ManagerService {
     pub fn init() -> Self {
-        unsafe { STATE = Some(ManagerState::new()) }
+        unsafe { STATE = Some(ManagerState::new(msg::source())) }
         Self(())
     }
     pub fn get_mut(&mut self) -> &'static mut ManagerState {
@@ -65,81 +228,320 @@ impl ManagerService {
     pub fn get(&self) -> &'static ManagerState {
         unsafe { STATE.as_ref().expect("STATE is not initialized") }
     }
+    fn ensure_owner(&self) -> Result<(), ManagerError> {
+        if msg::source() != self.get().owner {
+            return Err(ManagerError::NotOwner);
+        }
+        Ok(())
+    }
+    fn ensure_admin(&self) -> Result<(), ManagerError> {
+        if !self.get().is_admin(&msg::source()) {
+            return Err(ManagerError::NotAdmin);
+        }
+        Ok(())
+    }
+    /// Returns the job if the caller created it or is an admin.
+    fn job_mut(&mut self, job_id: JobId) -> Result<&'static mut RenderJob, ManagerError> {
+        let source = msg::source();
+        let state = self.get_mut();
+        let is_admin = state.is_admin(&source);
+        let job = state
+            .jobs
+            .get_mut(&job_id)
+            .ok_or(ManagerError::JobNotFound)?;
+        if job.creator != source && !is_admin {
+            return Err(ManagerError::NotJobCreator);
+        }
+        Ok(job)
+    }
 }
 
-#[sails_rs::service]
+#[sails_rs::service(events = Event)]
 impl ManagerService {
     pub fn new() -> Self {
         Self(())
     }
 
-    pub async fn add_checkers(&mut self, checkers: Vec<ActorId>) {
-        self.get_mut().checkers.extend(checkers);
+    /// Registers new checkers. Duplicates and the zero address are skipped.
+    /// Every checker is asked for its capabilities first, and none is
+    /// registered if one of them is not compatible. The caller becomes the
+    /// payee of their rewards.
+    pub async fn add_checkers(&mut self, checkers: Vec<ActorId>) -> Result<(), ManagerError> {
+        self.ensure_admin()?;
+        let payee = msg::source();
+        let mut compatible = Vec::with_capacity(checkers.len());
+        for checker in checkers {
+            if !self.get().checkers.can_add(&checker) {
+                continue;
+            }
+            let capabilities = query_capabilities(checker)
+                .await
+                .ok_or(ManagerError::IncompatibleChecker)?;
+            compatible.push((checker, capabilities));
+        }
+        let state = self.get_mut();
+        for (checker, capabilities) in compatible {
+            if state.checkers.add(checker, capabilities) {
+                state.rewards.set_payee(checker, payee);
+            }
+        }
+        Ok(())
     }
 
-    pub fn restart(&mut self) {
-        self.get_mut().point_results.clear();
-        self.get_mut().points_sent = 0;
+    /// Registers a checker that cannot answer the capabilities query, such as
+    /// an account that computes off-chain and replies with `result_calculated`.
+    pub fn add_checker_with_capabilities(
+        &mut self,
+        checker: ActorId,
+        capabilities: Capabilities,
+    ) -> Result<(), ManagerError> {
+        self.ensure_admin()?;
+        if !capabilities.is_compatible() {
+            return Err(ManagerError::IncompatibleChecker);
+        }
+        let state = self.get_mut();
+        if !state.checkers.add(checker, capabilities) {
+            return Err(ManagerError::CheckerAlreadyRegistered);
+        }
+        state.rewards.set_payee(checker, msg::source());
+        Ok(())
     }
-    pub fn generate_and_store_points(
+
+    /// Sets the code `spawn_checkers` and `scale_checkers` deploy checkers from.
+    pub fn set_checker_code(&mut self, code_id: CodeId) -> Result<(), ManagerError> {
+        self.ensure_admin()?;
+        self.get_mut().checker_code = Some(code_id);
+        Ok(())
+    }
+
+    /// Deploys `count` checkers from the checker code and registers the ones
+    /// that report compatible capabilities. The caller becomes the payee of
+    /// their rewards. The programs that are not compatible are left
+    /// unregistered and listed in `CheckersSpawned`. Deployment stops at the
+    /// first program that cannot be created, after announcing the checkers
+    /// deployed so far.
+    pub async fn spawn_checkers(&mut self, count: u32) -> Result<Vec<ActorId>, ManagerError> {
+        self.ensure_admin()?;
+        let payee = msg::source();
+        let code_id = self
+            .get()
+            .checker_code
+            .ok_or(ManagerError::CheckerCodeNotSet)?;
+        // The checker program has a single constructor without arguments.
+        let payload = "New".encode();
+        let mut checkers = Vec::with_capacity(count as usize);
+        let mut incompatible = Vec::new();
+        let mut result = Ok(());
+        for _ in 0..count {
+            let Ok((_, checker)) = ProgramGenerator::create_program_bytes(code_id, &payload, 0)
+            else {
+                result = Err(ManagerError::CheckerCreationFailed);
+                break;
+            };
+            // The query is handled after the constructor.
+            let Some(capabilities) = query_capabilities(checker).await else {
+                incompatible.push(checker);
+                continue;
+            };
+            let state = self.get_mut();
+            if state.checkers.add(checker, capabilities) {
+                state.rewards.set_payee(checker, payee);
+                state.spawned.push(checker);
+                checkers.push(checker);
+            }
+        }
+        self.notify_on(Event::CheckersSpawned {
+            checkers: checkers.clone(),
+            incompatible,
+        })
+        .expect("Notification Error");
+        result.map(|()| checkers)
+    }
+
+    /// Brings the checkers the manager deployed to `count`, deploying new ones
+    /// or removing the last deployed ones. Checkers registered by hand stay.
+    pub async fn scale_checkers(&mut self, count: u32) -> Result<(), ManagerError> {
+        self.ensure_admin()?;
+        let state = self.get_mut();
+        // Spawned checkers may have been removed or replaced since.
+        state
+            .spawned
+            .retain(|checker| state.checkers.contains(checker));
+        let current = state.spawned.len() as u32;
+        if count > current {
+            self.spawn_checkers(count - current).await?;
+            return Ok(());
+        }
+        let removed = state.spawned.split_off(count as usize);
+        for checker in removed {
+            if let Some(pending) = state.checkers.remove(&checker) {
+                state.requeue(pending);
+            }
+        }
+        Ok(())
+    }
+
+    pub fn get_checker_code(&self) -> Option<CodeId> {
+        self.get().checker_code
+    }
+
+    /// Unregisters checkers. The batches they have not answered yet are sent again
+    /// to the remaining checkers.
+    pub fn remove_checkers(&mut self, checkers: Vec<ActorId>) -> Result<(), ManagerError> {
+        self.ensure_admin()?;
+        let state = self.get_mut();
+        for checker in checkers {
+            if let Some(pending) = state.checkers.remove(&checker) {
+                state.requeue(pending);
+            }
+        }
+        Ok(())
+    }
+
+    /// Swaps a checker for another one keeping its position in the dispatch
+    /// order. The new checker is asked for its capabilities first.
+    pub async fn replace_checker(
         &mut self,
-        width: u32,
-        height: u32,
-        x_min: FixedPoint,
-        x_max: FixedPoint,
-        y_min: FixedPoint,
-        y_max: FixedPoint,
-        points_per_call: u32,
-        continue_generation: bool,
-        check_points_after_generation: bool,
-        max_iter: u32,
-        batch_size: u32,
-    ) {
-        let x_min_dec = Decimal::new(x_min.num, x_min.scale);
-        let x_max_dec = Decimal::new(x_max.num, x_max.scale);
-        let y_min_dec = Decimal::new(y_min.num, y_min.scale);
-        let y_max_dec = Decimal::new(y_max.num, y_max.scale);
+        old: ActorId,
+        new: ActorId,
+    ) -> Result<(), ManagerError> {
+        self.ensure_admin()?;
+        let payee = msg::source();
+        if !self.get().checkers.contains(&old) {
+            return Err(ManagerError::CheckerNotFound);
+        }
+        if !self.get().checkers.can_add(&new) {
+            return Err(ManagerError::CheckerAlreadyRegistered);
+        }
+        let capabilities = query_capabilities(new)
+            .await
+            .ok_or(ManagerError::IncompatibleChecker)?;
+        let state = self.get_mut();
+        let pending = state
+            .checkers
+            .replace(&old, new, capabilities)
+            .ok_or(ManagerError::CheckerNotFound)?;
+        state.rewards.set_payee(new, payee);
+        state.requeue(pending);
+        Ok(())
+    }
+
+    pub fn add_admin(&mut self, admin: ActorId) -> Result<(), ManagerError> {
+        self.ensure_owner()?;
+        let admins = &mut self.get_mut().admins;
+        if admins.contains(&admin) {
+            return Err(ManagerError::AlreadyAdmin);
+        }
+        admins.push(admin);
+        Ok(())
+    }
 
-        let scale_x = (x_max_dec - x_min_dec) / Decimal::from(width);
-        let scale_y = (y_max_dec - y_min_dec) / Decimal::from(height);
+    pub fn remove_admin(&mut self, admin: ActorId) -> Result<(), ManagerError> {
+        self.ensure_owner()?;
+        let admins = &mut self.get_mut().admins;
+        let Some(position) = admins.iter().position(|a| *a == admin) else {
+            return Err(ManagerError::NotAnAdmin);
+        };
+        admins.remove(position);
+        Ok(())
+    }
 
-        let total_points = width * height;
-        let total_generated_points = self.get_mut().point_results.len() as u32;
+    pub fn transfer_ownership(&mut self, new_owner: ActorId) -> Result<(), ManagerError> {
+        self.ensure_owner()?;
+        self.get_mut().owner = new_owner;
+        Ok(())
+    }
 
-        if total_generated_points >= total_points {
-            return;
+    /// Creates a render job. Any account can create jobs; its points are
+    /// generated with `generate_and_store_points` and checked together with the
+    /// points of the other jobs. The value sent with the message pays the
+    /// checkers. An account has at most `MAX_JOBS_PER_CREATOR` jobs and all
+    /// jobs together at most `MAX_LIVE_POINTS` points.
+    pub fn create_job(&mut self, params: JobParams) -> Result<JobId, ManagerError> {
+        if !params.is_valid() {
+            return Err(ManagerError::InvalidJobParams);
         }
+        let state = self.get_mut();
+        let creator = msg::source();
+        let jobs = state.jobs_per_creator.get(&creator).copied().unwrap_or(0);
+        if jobs >= MAX_JOBS_PER_CREATOR {
+            return Err(ManagerError::TooManyJobs);
+        }
+        let live_points = state.live_points + params.total_points();
+        if live_points > MAX_LIVE_POINTS {
+            return Err(ManagerError::TooManyPoints);
+        }
+        state.jobs_per_creator.insert(creator, jobs + 1);
+        state.live_points = live_points;
+        let job_id = state.next_job_id;
+        state.next_job_id += 1;
+        state.jobs.insert(
+            job_id,
+            RenderJob::new(creator, params, msg::value(), exec::block_height()),
+        );
+        self.notify_on(Event::JobCreated { job_id, creator })
+            .expect("Notification Error");
+        Ok(job_id)
+    }
 
-        let starting_index = total_generated_points;
+    /// Throws away the points and results of the job, keeping its parameters.
+    /// What is left of the budget pays for computing the points again.
+    pub fn restart_job(&mut self, job_id: JobId) -> Result<(), ManagerError> {
+        self.job_mut(job_id)?.reset();
+        self.get_mut().checkers.mark_pending_stale(job_id);
+        Ok(())
+    }
 
-        for i in starting_index..starting_index + points_per_call.min(total_points - starting_index)
-        {
-            let x = i / width;
-            let y = i % width;
+    /// Removes the job and sends what is left of its budget back to its creator.
+    pub fn remove_job(&mut self, job_id: JobId) -> Result<(), ManagerError> {
+        self.job_mut(job_id)?;
+        let state = self.get_mut();
+        let Some(mut job) = state.jobs.remove(&job_id) else {
+            return Err(ManagerError::JobNotFound);
+        };
+        state.checkers.mark_pending_stale(job_id);
+        state.live_points -= job.params.total_points();
+        if let Some(jobs) = state.jobs_per_creator.get_mut(&job.creator) {
+            *jobs -= 1;
+            if *jobs == 0 {
+                state.jobs_per_creator.remove(&job.creator);
+            }
+        }
+        let refund = job.take_budget();
+        if refund > 0 {
+            msg::send(job.creator, (), refund).expect("Error during refund");
+        }
+        Ok(())
+    }
 
-            let c_re = FixedPoint::from_decimal(x_min_dec + Decimal::from(x) * scale_x);
-            let c_im = FixedPoint::from_decimal(y_min_dec + Decimal::from(y) * scale_y);
+    pub fn generate_and_store_points(
+        &mut self,
+        job_id: JobId,
+        points_per_call: u32,
+        continue_generation: bool,
+        check_points_after_generation: bool,
+        batch_size: u32,
+    ) -> Result<(), ManagerError> {
+        let job = self.job_mut(job_id)?;
 
-            self.get_mut()
-                .point_results
-                .insert(i, (c_re, c_im, 0, false));
+        if job.is_generated() {
+            return Ok(());
         }
 
-        if continue_generation && total_generated_points < total_points {
+        job.generate(points_per_call);
+        let total = job.points_generated();
+        self.notify_on(Event::PointsGenerated { job_id, total })
+            .expect("Notification Error");
+
+        if continue_generation && !job.is_generated() {
             let payload = [
                 "Manager".encode(),
                 "GenerateAndStorePoints".encode(),
                 (
-                    width,
-                    height,
-                    x_min,
-                    x_max,
-                    y_min,
-                    y_max,
+                    job_id,
                     points_per_call,
                     continue_generation,
                     check_points_after_generation,
-                    max_iter,
                     batch_size,
                 )
                     .encode(),
@@ -148,120 +550,518 @@ impl ManagerService {
             msg::send_bytes(exec::program_id(), payload, 0).expect("Error during msg sending");
         }
 
-        if check_points_after_generation && self.get_mut().point_results.len() as u32 >= total_points {
+        if check_points_after_generation && job.is_generated() {
             let payload = [
                 "Manager".encode(),
                 "CheckPointsSet".encode(),
-                (max_iter, batch_size, true).encode(),
+                (batch_size, true).encode(),
             ]
             .concat();
             msg::send_bytes(exec::program_id(), payload, 0).expect("Error during msg sending");
         }
+        Ok(())
     }
 
-    pub fn check_points_set(&mut self, max_iter: u32, batch_size: u32, continue_checking: bool) {
-        let checkers = &self.get().checkers;
-        let points = &self.get().point_results;
+    /// Sends one batch to every checker, taking the batches from the active jobs in turn.
+    /// Batches left unanswered for `BATCH_TIMEOUT_BLOCKS` are sent again first.
+    pub fn check_points_set(
+        &mut self,
+        batch_size: u32,
+        continue_checking: bool,
+    ) -> Result<(), ManagerError> {
+        self.ensure_admin()?;
+        let state = self.get_mut();
+        let expired = state.checkers.take_expired(exec::block_height());
+        state.requeue(expired);
+        let checkers = self.get().checkers.list();
 
-        if checkers.is_empty() || points.is_empty() {
-            return;
+        if checkers.is_empty() || !self.get().has_points_to_send() {
+            return Ok(());
         }
 
+        // A checker may not support any of the remaining jobs, so every
+        // checker gets a chance.
+        let mut dispatched = false;
         for checker in checkers.iter() {
-            if self.get().points_sent >= points.len() as u32 {
+            if !self.get().has_points_to_send() {
                 break;
             }
-            self.send_next_batch(*checker, max_iter, batch_size);
+            dispatched |= self.send_batch(*checker, batch_size);
         }
-        if continue_checking && self.get().points_sent < self.get().point_results.len() as u32 {
+        if continue_checking && dispatched && self.get().has_points_to_send() {
             let payload = [
                 "Manager".encode(),
                 "CheckPointsSet".encode(),
-                (max_iter, batch_size, continue_checking).encode(),
+                (batch_size, continue_checking).encode(),
             ]
             .concat();
             msg::send_bytes(exec::program_id(), payload, 0).expect("Error during msg sending");
         }
+        Ok(())
     }
 
-    pub fn send_next_batch(&mut self, checker: ActorId, max_iter: u32, batch_size: u32) {
-        let mut points_to_send = Vec::new();
+    pub fn send_next_batch(
+        &mut self,
+        checker: ActorId,
+        batch_size: u32,
+    ) -> Result<(), ManagerError> {
+        self.ensure_admin()?;
+        if !self.get().checkers.contains(&checker) {
+            return Err(ManagerError::UnknownChecker);
+        }
+        self.send_batch(checker, batch_size);
+        Ok(())
+    }
+
+    /// The reply of a checker to the batch `batch_id`.
+    pub fn result_calculated(
+        &mut self,
+        batch_id: u64,
+        indexes: Vec<u32>,
+        results: Vec<u32>,
+        smooth: Vec<FixedPoint>,
+    ) -> Result<(), ManagerError> {
+        let checker = msg::source();
+        let state = self.get_mut();
+        if !state.checkers.contains(&checker) {
+            return Err(ManagerError::UnknownChecker);
+        }
+        let Some(batch) = state
+            .checkers
+            .take_batch(&checker, batch_id, exec::block_height())
+        else {
+            state.checkers.record_failure(&checker);
+            return Err(ManagerError::InvalidResults);
+        };
+        if batch.stale {
+            return Ok(());
+        }
 
-        let start = self.get().points_sent as u32;
-        let end = start + batch_size;
+        let smooth_matches = state
+            .jobs
+            .get(&batch.job_id)
+            .is_some_and(|job| job.is_valid_smooth(indexes.len(), &smooth));
+        if indexes != batch.indexes || results.len() != indexes.len() || !smooth_matches {
+            state.checkers.record_failure(&checker);
+            state.requeue(vec![batch]);
+            return Err(ManagerError::InvalidResults);
+        }
 
-        for i in start..end {
-            if let Some((c_re, c_im, _, _)) = self.get().point_results.get(&i) {
-                points_to_send.push(Point {
-                    index: i,
-                    c_re: c_re.clone(),
-                    c_im: c_im.clone(),
-                });
+        let count = indexes.len() as u32;
+        let job_id = batch.job_id;
+        state.checkers.record_success(&checker, count);
+        // The budget pays for every point once, so only the batches that
+        // compute new results are credited.
+        if let (BatchKind::Compute, Some(job)) = (&batch.kind, state.jobs.get_mut(&job_id)) {
+            state.rewards.credit(checker, job.pay(count));
+        }
+        match batch.kind {
+            BatchKind::Compute => {}
+            BatchKind::Verify {
+                checker: original,
+                results: expected,
+            } => {
+                self.open_disputes(job_id, original, expected, checker, indexes, results);
+                return Ok(());
+            }
+            BatchKind::Arbitrate { disputes } => {
+                self.settle_disputes(disputes, results, smooth);
+                return Ok(());
             }
         }
 
-        if points_to_send.is_empty() {
-            return;
+        self.verify_sample(job_id, checker, &indexes, &results);
+        let state = self.get_mut();
+        let render_duration = state
+            .jobs
+            .get_mut(&job_id)
+            .and_then(|job| job.record_results(indexes, results, smooth, exec::block_height()));
+        // The results of an adaptive job may queue new points after the
+        // dispatch loop has stopped, so the checker gets them right away.
+        let refill = state
+            .jobs
+            .get(&job_id)
+            .filter(|job| job.params.adaptive && job.has_points_to_send())
+            .map(|job| job.last_batch_size());
+
+        self.notify_on(Event::BatchCompleted {
+            job_id,
+            checker,
+            count,
+        })
+        .expect("Notification Error");
+        if let Some(duration_blocks) = render_duration {
+            self.notify_on(Event::RenderCompleted {
+                job_id,
+                duration_blocks,
+            })
+            .expect("Notification Error");
+        }
+        if let Some(batch_size) = refill {
+            self.send_batch(checker, batch_size);
         }
+        Ok(())
+    }
 
-        self.get_mut().points_sent += points_to_send.len() as u32;
+    /// Sets how many points of every batch are computed again by a second
+    /// checker and how many lost disputes get a checker removed.
+    pub fn set_verification(&mut self, config: VerificationConfig) -> Result<(), ManagerError> {
+        self.ensure_admin()?;
+        self.get_mut().verification = config;
+        Ok(())
+    }
 
-        let payload = [
-            "MandelbrotChecker".encode(),
-            "CheckMandelbrotPoints".encode(),
-            (points_to_send, max_iter).encode(),
-        ]
-        .concat();
+    pub fn get_verification(&self) -> VerificationConfig {
+        self.get().verification.clone()
+    }
 
-        msg::send_bytes(checker, payload, 0).expect("Failed to send points to checker");
+    /// The disputes over points of the job, in the order they were opened.
+    pub fn get_disputes(&self, job_id: JobId) -> Vec<Dispute> {
+        self.get()
+            .disputes
+            .iter()
+            .filter(|dispute| dispute.job_id == job_id)
+            .cloned()
+            .collect()
     }
 
-    pub fn result_calculated(&mut self, indexes: Vec<u32>, results: Vec<u32>) {
-        // sails_rs::gstd::debug!("Received indexes from {} to {}", indexes[0], indexes[19]);
-        indexes
-            .into_iter()
-            .zip(results)
-            .for_each(|(index, result)| {
-                if let Some(point) = self.get_mut().point_results.get_mut(&index) {
-                    point.2 = result;
-                    point.3 = true;
-                }
-            });
+    /// Sets the account that withdraws the rewards of the checker. Callable by
+    /// its current payee or an admin.
+    pub fn set_payee(&mut self, checker: ActorId, payee: ActorId) -> Result<(), ManagerError> {
+        let source = msg::source();
+        let state = self.get_mut();
+        if state.rewards.payee(&checker) != Some(source) && !state.is_admin(&source) {
+            return Err(ManagerError::NotPayee);
+        }
+        state.rewards.set_payee(checker, payee);
+        Ok(())
+    }
+
+    /// Sends the reward balance of the checker to its payee and returns the amount.
+    pub fn withdraw(&mut self, checker: ActorId) -> Result<u128, ManagerError> {
+        let payee = msg::source();
+        let rewards = &mut self.get_mut().rewards;
+        if rewards.payee(&checker) != Some(payee) {
+            return Err(ManagerError::NotPayee);
+        }
+        let amount = rewards.take(&checker);
+        if amount == 0 {
+            return Err(ManagerError::NothingToWithdraw);
+        }
+        msg::send(payee, (), amount).expect("Error during withdrawal");
+        Ok(amount)
+    }
+
+    pub fn get_balance(&self, checker: ActorId) -> u128 {
+        self.get().rewards.balance(&checker)
+    }
+
+    pub fn get_payee(&self, checker: ActorId) -> Option<ActorId> {
+        self.get().rewards.payee(&checker)
+    }
+
+    pub fn owner(&self) -> ActorId {
+        self.get().owner
+    }
+
+    pub fn admins(&self) -> Vec<ActorId> {
+        self.get().admins.clone()
+    }
+
+    pub fn get_job(&self, job_id: JobId) -> Option<JobInfo> {
+        self.get().jobs.get(&job_id).map(|job| job.info(job_id))
+    }
+
+    /// Parameters the job was created with, to size and map an image of its
+    /// results.
+    pub fn get_job_params(&self, job_id: JobId) -> Option<JobParams> {
+        self.get().jobs.get(&job_id).map(|job| job.params.clone())
+    }
+
+    pub fn get_jobs(&self) -> Vec<JobInfo> {
+        self.get()
+            .jobs
+            .iter()
+            .map(|(id, job)| job.info(*id))
+            .collect()
     }
 
-    pub fn get_points_len(&self) -> u32 {
-        self.get().point_results.len() as u32
+    pub fn get_points_len(&self, job_id: JobId) -> u32 {
+        self.get()
+            .jobs
+            .get(&job_id)
+            .map_or(0, |job| job.points_generated())
     }
 
     pub fn get_checkers(&self) -> Vec<ActorId> {
-        self.get().checkers.clone()
+        self.get().checkers.list().to_vec()
+    }
+
+    pub fn get_checker_capabilities(&self, checker: ActorId) -> Option<Capabilities> {
+        self.get().checkers.capabilities(&checker).cloned()
     }
 
-    pub fn points_sent(&self) -> u32 {
-        self.get().points_sent
+    pub fn get_checker_stats(&self, checker: ActorId) -> Option<CheckerStats> {
+        self.get().checkers.stats(&checker)
     }
 
-    pub fn get_results(&self, start_index: u32, end_index: u32) -> Vec<PointResult> {
-        let results = &self.get().point_results;
+    pub fn get_all_checker_stats(&self) -> Vec<(ActorId, CheckerStats)> {
+        self.get().checkers.all_stats()
+    }
+
+    pub fn points_sent(&self, job_id: JobId) -> u32 {
+        self.get()
+            .jobs
+            .get(&job_id)
+            .map_or(0, |job| job.points_sent())
+    }
 
-        results
+    /// Returns up to `limit` results starting at index `cursor`. The limit is capped
+    /// at `MAX_RESULTS_PAGE_SIZE` and raised to 1, so a page that is not the last
+    /// one always moves `next_cursor` forward.
+    pub fn get_results(
+        &self,
+        job_id: JobId,
+        cursor: u32,
+        limit: u32,
+    ) -> Result<ResultsPage, ManagerError> {
+        let job = self
+            .get()
+            .jobs
+            .get(&job_id)
+            .ok_or(ManagerError::JobNotFound)?;
+        Ok(job.results(cursor, limit.clamp(1, MAX_RESULTS_PAGE_SIZE)))
+    }
+
+    /// Sends the next batch of the next scheduled job to the checker, of at
+    /// most `batch_size` points and at most what the checker takes. Returns
+    /// `false` when no job the checker supports has points left to send.
+    fn send_batch(&mut self, checker: ActorId, batch_size: u32) -> bool {
+        let state = self.get_mut();
+        let batch_size = state.checkers.batch_size(&checker, batch_size);
+        let Some(job_id) = state.next_scheduled_job(&checker) else {
+            return false;
+        };
+        let job = state.jobs.get_mut(&job_id).expect("Scheduled job exists");
+        let indexes = job.next_batch(batch_size, exec::block_height());
+
+        if indexes.is_empty() {
+            return false;
+        }
+
+        let count = indexes.len() as u32;
+        self.dispatch(checker, job_id, indexes, BatchKind::Compute);
+        self.notify_on(Event::BatchDispatched {
+            job_id,
+            checker,
+            count,
+        })
+        .expect("Notification Error");
+        true
+    }
+
+    /// Sends the points of a job to a checker and records the pending batch.
+    fn dispatch(&mut self, checker: ActorId, job_id: JobId, indexes: Vec<u32>, kind: BatchKind) {
+        let state = self.get_mut();
+        let Some(job) = state.jobs.get(&job_id) else {
+            return;
+        };
+        let points_to_send = job.points(&indexes);
+        let id = state.next_batch_id;
+        state.next_batch_id += 1;
+
+        let payload = [
+            "MandelbrotChecker".encode(),
+            "CheckMandelbrotPoints".encode(),
+            (
+                id,
+                points_to_send,
+                job.params.max_iter,
+                job.params.smooth,
+                job.params.fractal.clone(),
+                job.reference.clone(),
+            )
+                .encode(),
+        ]
+        .concat();
+
+        msg::send_bytes(checker, payload, 0).expect("Failed to send points to checker");
+        let block = exec::block_height();
+        state.checkers.dispatched(
+            checker,
+            PendingBatch {
+                id,
+                job_id,
+                indexes,
+                stale: false,
+                kind,
+                dispatched_at: block,
+            },
+            block,
+        );
+    }
+
+    /// Sends a random sample of the answered batch to another checker.
+    fn verify_sample(&mut self, job_id: JobId, checker: ActorId, indexes: &[u32], results: &[u32]) {
+        let state = self.get();
+        if state.verification.sample_size == 0 {
+            return;
+        }
+        let Some(job) = state.jobs.get(&job_id) else {
+            return;
+        };
+        let Some(verifier) = state.checkers.next_other(&checker, &[checker], &job.params) else {
+            return;
+        };
+        // The sample has to fit into a single batch of the verifier.
+        let sample_size = state
+            .checkers
+            .batch_size(&verifier, state.verification.sample_size);
+        if sample_size == 0 {
+            return;
+        }
+        // The reply message keeps the subject distinct between the replies
+        // processed in the same block.
+        let (random, _) =
+            exec::random(msg::id().into_bytes()).expect("Error during randomness query");
+        let seed = u64::from_le_bytes(
+            random[..8]
+                .try_into()
+                .expect("The random hash has 32 bytes"),
+        );
+        let positions = verification::sample(indexes.len(), sample_size, seed);
+        let sample = positions
             .iter()
-            .filter_map(|(&index, &(ref c_re, ref c_im, iter, checked))| {
-                if index >= start_index && index < end_index {
-                    Some(PointResult {
-                        c_re: c_re.clone(),
-                        c_im: c_im.clone(),
-                        iter,
-                        checked,
-                    })
-                } else {
-                    None
+            .map(|&position| indexes[position])
+            .collect();
+        let expected = positions
+            .iter()
+            .map(|&position| results[position])
+            .collect();
+        self.dispatch(
+            verifier,
+            job_id,
+            sample,
+            BatchKind::Verify {
+                checker,
+                results: expected,
+            },
+        );
+    }
+
+    /// Opens a dispute for every point of a sample the verifier computed
+    /// differently and sends them to a third checker when there is one.
+    fn open_disputes(
+        &mut self,
+        job_id: JobId,
+        checker: ActorId,
+        expected: Vec<u32>,
+        verifier: ActorId,
+        indexes: Vec<u32>,
+        results: Vec<u32>,
+    ) {
+        let state = self.get_mut();
+        let mut contested = Vec::new();
+        let mut disputes = Vec::new();
+        for ((index, checker_iter), verifier_iter) in indexes.into_iter().zip(expected).zip(results)
+        {
+            if checker_iter == verifier_iter {
+                continue;
+            }
+            contested.push(index);
+            disputes.push(state.disputes.len() as u32);
+            state.disputes.push(Dispute {
+                job_id,
+                index,
+                checker,
+                checker_iter,
+                verifier,
+                verifier_iter,
+                status: DisputeStatus::Open,
+            });
+        }
+        if contested.is_empty() {
+            return;
+        }
+
+        self.notify_on(Event::DisputesOpened {
+            job_id,
+            checker,
+            verifier,
+            count: contested.len() as u32,
+        })
+        .expect("Notification Error");
+        let arbiter = state.jobs.get(&job_id).and_then(|job| {
+            state
+                .checkers
+                .next_other(&verifier, &[checker, verifier], &job.params)
+        });
+        if let Some(arbiter) = arbiter {
+            self.dispatch(
+                arbiter,
+                job_id,
+                contested,
+                BatchKind::Arbitrate { disputes },
+            );
+        }
+    }
+
+    /// Settles disputes with the results of the arbiter: the job keeps the
+    /// result of the side it agrees with, and the other side is flagged.
+    fn settle_disputes(&mut self, disputes: Vec<u32>, results: Vec<u32>, smooth: Vec<FixedPoint>) {
+        let state = self.get_mut();
+        let mut slashed = Vec::new();
+        // `smooth` is empty unless the job computes smooth counts.
+        let mut smooth = smooth.into_iter();
+        for (position, arbiter_iter) in disputes.into_iter().zip(results) {
+            let arbiter_smooth = smooth.next();
+            let Some(dispute) = state.disputes.get_mut(position as usize) else {
+                continue;
+            };
+            let Some(faulty) = dispute.settle(arbiter_iter) else {
+                continue;
+            };
+            // The reward of the wrong result goes back to the job.
+            if let Some(job) = state.jobs.get_mut(&dispute.job_id) {
+                if faulty == dispute.checker {
+                    job.correct_result(dispute.index, arbiter_iter, arbiter_smooth);
                 }
+                job.refund(state.rewards.debit(&faulty, job.reward_per_point()));
+            }
+            let disputes_lost = state.checkers.record_lost_dispute(&faulty);
+            let max_disputes = state.verification.max_disputes;
+            if max_disputes > 0 && disputes_lost >= max_disputes {
+                if let Some(pending) = state.checkers.remove(&faulty) {
+                    state.requeue(pending);
+                    // The balance of a slashed checker stays with the manager.
+                    state.rewards.take(&faulty);
+                    slashed.push((faulty, disputes_lost));
+                }
+            }
+        }
+        for (checker, disputes_lost) in slashed {
+            self.notify_on(Event::CheckerSlashed {
+                checker,
+                disputes_lost,
             })
-            .collect()
+            .expect("Notification Error");
+        }
     }
 }
 
+/// Asks a checker for its capabilities. Checkers that do not answer, or answer
+/// with capabilities the manager cannot use, get `None`.
+async fn query_capabilities(checker: ActorId) -> Option<Capabilities> {
+    let payload = ["MandelbrotChecker".encode(), "Capabilities".encode()].concat();
+    let reply = msg::send_bytes_for_reply(checker, payload, 0, 0)
+        .ok()?
+        .await
+        .ok()?;
+    let (_service, _method, capabilities) =
+        <(String, String, Capabilities)>::decode(&mut reply.as_slice()).ok()?;
+    capabilities.is_compatible().then_some(capabilities)
+}
+
 pub struct ManagerProgram(());
 
 #[sails_rs::program]
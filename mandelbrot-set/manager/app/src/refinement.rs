@@ -0,0 +1,167 @@
+//! Adaptive refinement of a render job (Mariani-Silver, or "rectangle fill").
+//!
+//! The image is cut into tiles of `TILE_SIZE` pixels and at first only their
+//! corners are sent to the checkers. A tile whose four corners escape after the
+//! same number of iterations is taken as uniform: its other pixels are filled
+//! with that count without being dispatched. Any other tile is split in four
+//! and the corners of the quarters are sent next. Tiles down to 2x2 pixels are
+//! made of corners only, so the pixels near the boundary of the set are all
+//! computed.
+//!
+//! Details smaller than a tile that none of its corners hit, such as thin
+//! filaments of the Mandelbrot set, are filled over.
+
+use sails_rs::{collections::VecDeque, prelude::*};
+
+/// Distance in pixels between the corners of the coarse grid.
+pub const TILE_SIZE: u32 = 16;
+
+/// A rectangle of pixels between two corners, both included.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Tile {
+    pub x0: u32,
+    pub y0: u32,
+    pub x1: u32,
+    pub y1: u32,
+}
+
+impl Tile {
+    fn corners(&self) -> [(u32, u32); 4] {
+        [
+            (self.x0, self.y0),
+            (self.x1, self.y0),
+            (self.x0, self.y1),
+            (self.x1, self.y1),
+        ]
+    }
+
+    /// Whether the tile has pixels besides its corners.
+    fn has_inner_pixels(&self) -> bool {
+        self.x1 - self.x0 > 1 || self.y1 - self.y0 > 1
+    }
+
+    fn split(&self) -> Vec<Tile> {
+        let (columns, rows) = (halves(self.x0, self.x1), halves(self.y0, self.y1));
+        rows.iter()
+            .flat_map(|&(y0, y1)| columns.iter().map(move |&(x0, x1)| Tile { x0, y0, x1, y1 }))
+            .collect()
+    }
+}
+
+/// The two halves of a span of more than two pixels, sharing the middle one.
+fn halves(start: u32, end: u32) -> Vec<(u32, u32)> {
+    if end - start > 1 {
+        let middle = start + (end - start) / 2;
+        vec![(start, middle), (middle, end)]
+    } else {
+        vec![(start, end)]
+    }
+}
+
+/// Spans of at most `TILE_SIZE` pixels covering `0..len`.
+fn coarse_spans(len: u32) -> Vec<(u32, u32)> {
+    let mut lines: Vec<u32> = (0..len).step_by(TILE_SIZE as usize).collect();
+    if lines.last() != Some(&(len - 1)) {
+        lines.push(len - 1);
+    }
+    if lines.len() == 1 {
+        return vec![(0, 0)];
+    }
+    lines.windows(2).map(|pair| (pair[0], pair[1])).collect()
+}
+
+/// The tiles of an adaptive job and the pixels that still have to be sent.
+pub struct Refinement {
+    width: u32,
+    /// Tiles waiting for the results of their corners.
+    tiles: Vec<Tile>,
+    /// Pixels to send to the checkers, in the order they were requested.
+    queue: VecDeque<u32>,
+    /// One bit per pixel, set once it is queued.
+    requested: Vec<u64>,
+}
+
+impl Refinement {
+    /// Starts with the corners of the coarse grid of a `width` x `height` image.
+    pub fn new(width: u32, height: u32) -> Self {
+        let mut refinement = Self {
+            width,
+            tiles: Vec::new(),
+            queue: VecDeque::new(),
+            requested: vec![0; (width as usize * height as usize).div_ceil(64)],
+        };
+        let (columns, rows) = (coarse_spans(width), coarse_spans(height));
+        for &(y0, y1) in &rows {
+            for &(x0, x1) in &columns {
+                refinement.add_tile(Tile { x0, y0, x1, y1 });
+            }
+        }
+        refinement
+    }
+
+    fn index(&self, (x, y): (u32, u32)) -> u32 {
+        y * self.width + x
+    }
+
+    fn add_tile(&mut self, tile: Tile) {
+        for corner in tile.corners() {
+            let index = self.index(corner);
+            let (word, bit) = (index as usize / 64, 1 << (index % 64));
+            if self.requested[word] & bit == 0 {
+                self.requested[word] |= bit;
+                self.queue.push_back(index);
+            }
+        }
+        if tile.has_inner_pixels() {
+            self.tiles.push(tile);
+        }
+    }
+
+    pub fn has_points_to_send(&self) -> bool {
+        !self.queue.is_empty()
+    }
+
+    /// Takes up to `count` queued pixels, skipping the ones already known.
+    pub fn next_points(&mut self, count: usize, is_checked: impl Fn(u32) -> bool) -> Vec<u32> {
+        let mut indexes = Vec::new();
+        while indexes.len() < count {
+            let Some(index) = self.queue.pop_front() else {
+                break;
+            };
+            if !is_checked(index) {
+                indexes.push(index);
+            }
+        }
+        indexes
+    }
+
+    /// Settles the tiles whose corners are all known: uniform tiles are
+    /// returned to be filled, the others are split and their new corners
+    /// queued.
+    pub fn refine(
+        &mut self,
+        is_checked: impl Fn(u32) -> bool,
+        iter: impl Fn(u32) -> u32,
+    ) -> Vec<Tile> {
+        let width = self.width;
+        let mut uniform = Vec::new();
+        let mut mixed = Vec::new();
+        self.tiles.retain(|tile| {
+            let corners = tile.corners().map(|(x, y)| y * width + x);
+            if !corners.iter().all(|&index| is_checked(index)) {
+                return true;
+            }
+            let first = iter(corners[0]);
+            if corners.iter().all(|&index| iter(index) == first) {
+                uniform.push(*tile);
+            } else {
+                mixed.push(*tile);
+            }
+            false
+        });
+        for quarter in mixed.iter().flat_map(Tile::split) {
+            self.add_tile(quarter);
+        }
+        uniform
+    }
+}
@@ -0,0 +1,51 @@
+//! Reward balances of the checkers.
+//!
+//! A job is funded with the value sent to `create_job`, split evenly between
+//! its points. Every batch of new points a checker answers is credited at the
+//! rate of its job. Verification samples and arbitration compute points that
+//! were already paid for, so they are not credited. A checker cannot spend
+//! value itself, so its balance is withdrawn by its payee: the account that
+//! registered it unless changed with `set_payee`.
+
+use sails_rs::{collections::HashMap, prelude::*};
+
+#[derive(Default)]
+pub struct Rewards {
+    balances: HashMap<ActorId, u128>,
+    payees: HashMap<ActorId, ActorId>,
+}
+
+impl Rewards {
+    pub fn balance(&self, checker: &ActorId) -> u128 {
+        self.balances.get(checker).copied().unwrap_or_default()
+    }
+
+    pub fn payee(&self, checker: &ActorId) -> Option<ActorId> {
+        self.payees.get(checker).copied()
+    }
+
+    pub fn set_payee(&mut self, checker: ActorId, payee: ActorId) {
+        self.payees.insert(checker, payee);
+    }
+
+    pub fn credit(&mut self, checker: ActorId, amount: u128) {
+        if amount > 0 {
+            *self.balances.entry(checker).or_default() += amount;
+        }
+    }
+
+    /// Takes up to `amount` back from the checker and returns what was taken.
+    pub fn debit(&mut self, checker: &ActorId, amount: u128) -> u128 {
+        let Some(balance) = self.balances.get_mut(checker) else {
+            return 0;
+        };
+        let taken = amount.min(*balance);
+        *balance -= taken;
+        taken
+    }
+
+    /// Empties the balance of the checker and returns it.
+    pub fn take(&mut self, checker: &ActorId) -> u128 {
+        self.balances.remove(checker).unwrap_or_default()
+    }
+}
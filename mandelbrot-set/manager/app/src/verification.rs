@@ -0,0 +1,87 @@
+//! Redundant sampling of checker results.
+//!
+//! With a `sample_size`, part of every answered batch is sent again to another
+//! checker. The points on which the two checkers disagree become disputes and
+//! are sent to a third checker: the side the arbiter disagrees with loses the
+//! dispute, and the result of the job is corrected if needed. Checkers are
+//! flagged with the disputes they lost and removed from the pool after
+//! `max_disputes`.
+
+use crate::job::JobId;
+use sails_rs::prelude::*;
+
+/// Verification settings of the manager. Verification is off by default.
+#[derive(Encode, Decode, TypeInfo, Clone, Default, Debug, PartialEq, Eq)]
+pub struct VerificationConfig {
+    /// Points of every batch sent again to a second checker, 0 to trust the checkers.
+    pub sample_size: u32,
+    /// Lost disputes after which a checker is removed, 0 to only flag it.
+    pub max_disputes: u32,
+}
+
+#[derive(Encode, Decode, TypeInfo, Clone, Debug, PartialEq, Eq)]
+pub enum DisputeStatus {
+    /// Waiting for a third checker.
+    Open,
+    /// The arbiter agreed with one side, `faulty` is the other one.
+    Resolved { faulty: ActorId },
+    /// The arbiter agreed with neither side.
+    Unresolved,
+}
+
+/// A point that two checkers computed differently.
+#[derive(Encode, Decode, TypeInfo, Clone, Debug, PartialEq, Eq)]
+pub struct Dispute {
+    pub job_id: JobId,
+    pub index: u32,
+    pub checker: ActorId,
+    pub checker_iter: u32,
+    pub verifier: ActorId,
+    pub verifier_iter: u32,
+    pub status: DisputeStatus,
+}
+
+impl Dispute {
+    /// Settles the dispute with the result of the arbiter and returns the
+    /// checker at fault, if any.
+    pub fn settle(&mut self, arbiter_iter: u32) -> Option<ActorId> {
+        let faulty = if arbiter_iter == self.verifier_iter {
+            Some(self.checker)
+        } else if arbiter_iter == self.checker_iter {
+            Some(self.verifier)
+        } else {
+            None
+        };
+        self.status = match faulty {
+            Some(faulty) => DisputeStatus::Resolved { faulty },
+            None => DisputeStatus::Unresolved,
+        };
+        faulty
+    }
+}
+
+/// Picks `size` distinct positions out of `len`.
+///
+/// The manager seeds it with the runtime randomness of the block the reply is
+/// processed in, so the checker cannot tell which of its results will be
+/// computed again when it sends them.
+pub fn sample(len: usize, size: u32, seed: u64) -> Vec<usize> {
+    // A splitmix64 step spreads the few bits that differ between seeds.
+    let mut state = seed.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    state = (state ^ (state >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    state = (state ^ (state >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    state = (state ^ (state >> 31)) | 1;
+    let mut positions: Vec<usize> = (0..len).collect();
+    let size = (size as usize).min(len);
+    // Partial Fisher-Yates shuffle driven by xorshift64.
+    for i in 0..size {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        let j = i + (state % (len - i) as u64) as usize;
+        positions.swap(i, j);
+    }
+    positions.truncate(size);
+    positions.sort_unstable();
+    positions
+}
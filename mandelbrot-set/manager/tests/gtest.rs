@@ -1,60 +1,133 @@
+use futures::{FutureExt, StreamExt};
 use sails_rs::{
     calls::*,
+    events::*,
     gtest::{calls::*, System},
     prelude::*,
 };
 
-use manager_client::{traits::*, FixedPoint};
+use manager_client::{
+    manager::events::ManagerEvents, traits::*, Arithmetic, BigFixedPoint, Capabilities, DeepZoom,
+    DisputeStatus, FixedPoint, Fractal, FractalKind, JobParams, JobStatus, ManagerError,
+    VerificationConfig,
+};
 use mandelbrot_checker_client::traits::*;
 const ACTOR_ID: u64 = 42;
+const USER_ID: u64 = 43;
+/// `manager_app::BATCH_TIMEOUT_BLOCKS`.
+const BATCH_TIMEOUT_BLOCKS: u32 = 100;
+/// `mandelbrot_common::kernel::SMOOTH_SCALE`.
+const SMOOTH_SCALE: u32 = 9;
+/// `manager_app::MAX_JOBS_PER_CREATOR`.
+const MAX_JOBS_PER_CREATOR: u32 = 4;
+/// `manager_app::MAX_LIVE_POINTS`.
+const MAX_LIVE_POINTS: u32 = 16_000_000;
 
-#[tokio::test]
-async fn generate_and_store_points() {
+fn job_params(width: u32, height: u32, max_iter: u32) -> JobParams {
+    JobParams {
+        fractal: Fractal::Mandelbrot,
+        width,
+        height,
+        x_min: FixedPoint { num: -2, scale: 0 },
+        x_max: FixedPoint { num: 1, scale: 0 },
+        y_min: FixedPoint { num: -15, scale: 1 },
+        y_max: FixedPoint { num: 15, scale: 1 },
+        max_iter,
+        smooth: false,
+        deep_zoom: None,
+        adaptive: false,
+    }
+}
+
+/// Capabilities of a checker that only computes the Mandelbrot set.
+fn mandelbrot_capabilities(max_batch_size: u32) -> Capabilities {
+    Capabilities {
+        version: 2,
+        fractals: vec![FractalKind::Mandelbrot],
+        arithmetic: vec![Arithmetic::FixedPoint],
+        max_batch_size,
+        max_iter: 1000,
+    }
+}
+
+/// A fresh system with the test accounts funded, the manager deployed and
+/// `checkers` checker programs deployed but not registered.
+struct Setup {
+    remoting: GTestRemoting,
+    program_id: ActorId,
+    checker_code_id: CodeId,
+    checkers: Vec<ActorId>,
+}
+
+async fn setup(checkers: usize) -> Setup {
     let system = System::new();
     system.init_logger();
-    system.mint_to(ACTOR_ID, 100_000_000_000_000);
+    system.mint_to(ACTOR_ID, 1_000_000_000_000_000);
+    system.mint_to(USER_ID, 1_000_000_000_000_000);
 
     let remoting = GTestRemoting::new(system, ACTOR_ID.into());
-    remoting.system().init_logger();
 
     // Submit program code into the system
     let program_code_id = remoting.system().submit_code(manager::WASM_BINARY);
+    let checker_code_id = remoting
+        .system()
+        .submit_code(mandelbrot_checker::WASM_BINARY);
 
-    let program_factory = manager_client::ManagerFactory::new(remoting.clone());
+    let checker_factory =
+        mandelbrot_checker_client::MandelbrotCheckerFactory::new(remoting.clone());
+    let mut checker_ids = Vec::with_capacity(checkers);
+    for i in 0..checkers as u32 {
+        let checker_id = checker_factory
+            .new()
+            .send_recv(checker_code_id, i.to_le_bytes())
+            .await
+            .unwrap();
+        checker_ids.push(checker_id);
+    }
 
+    let program_factory = manager_client::ManagerFactory::new(remoting.clone());
     let program_id = program_factory
         .new()
         .send_recv(program_code_id, b"salt")
         .await
         .unwrap();
 
+    Setup {
+        remoting,
+        program_id,
+        checker_code_id,
+        checkers: checker_ids,
+    }
+}
+
+#[tokio::test]
+async fn generate_and_store_points() {
+    let Setup {
+        remoting,
+        program_id,
+        ..
+    } = setup(0).await;
+
     let mut service_client = manager_client::Manager::new(remoting.clone());
 
-    let width = 600;
-    let height = 600;
+    let job_id = service_client
+        .create_job(job_params(600, 600, 1000))
+        .send_recv(program_id)
+        .await
+        .unwrap()
+        .unwrap();
 
     for _i in 0..12 {
         service_client
-            .generate_and_store_points(
-                width,
-                height,
-                FixedPoint { num: -2, scale: 0 },
-                FixedPoint { num: 1, scale: 0 },
-                FixedPoint { num: -15, scale: 2 },
-                FixedPoint { num: 15, scale: 1 },
-                30_000,
-                false,
-                true,
-                1000,
-                20,
-            )
+            .generate_and_store_points(job_id, 30_000, false, true, 20)
             .send_recv(program_id)
             .await
+            .unwrap()
             .unwrap();
     }
 
     let points_len = service_client
-        .get_points_len()
+        .get_points_len(job_id)
         .recv(program_id)
         .await
         .unwrap();
@@ -64,91 +137,50 @@ async fn generate_and_store_points() {
 
 #[tokio::test]
 async fn add_checkers() {
-    let system = System::new();
-    system.init_logger();
-    system.mint_to(ACTOR_ID, 200_000_000_000_000);
-
-    let remoting = GTestRemoting::new(system, ACTOR_ID.into());
-    remoting.system().init_logger();
-
-    // Submit program code into the system
-    let program_code_id = remoting.system().submit_code(manager::WASM_BINARY);
-    let checker_code_id = remoting
-        .system()
-        .submit_code(mandelbrot_checker::WASM_BINARY);
-
-    let checker_factory =
-        mandelbrot_checker_client::MandelbrotCheckerFactory::new(remoting.clone());
-    let mut checkers: Vec<ActorId> = Vec::new();
+    let Setup {
+        remoting,
+        program_id,
+        checkers,
+        ..
+    } = setup(100).await;
 
-    for i in 0..100 {
-        let program_id = checker_factory
-            .new()
-            .send_recv(checker_code_id, &[i])
-            .await
-            .unwrap();
-        checkers.push(program_id.into());
-    }
+    let mut service_client = manager_client::Manager::new(remoting.clone());
 
-    let program_factory = manager_client::ManagerFactory::new(remoting.clone());
+    // Duplicates and the zero address are skipped
+    let mut with_duplicates = checkers.clone();
+    with_duplicates.extend_from_slice(&checkers[..10]);
+    with_duplicates.push(ActorId::zero());
 
-    let program_id = program_factory
-        .new()
-        .send_recv(program_code_id, b"salt")
+    service_client
+        .add_checkers(with_duplicates)
+        .send_recv(program_id)
         .await
+        .unwrap()
         .unwrap();
 
-    let mut service_client = manager_client::Manager::new(remoting.clone());
-
     service_client
-        .add_checkers(checkers)
+        .add_checkers(checkers.clone())
         .send_recv(program_id)
         .await
+        .unwrap()
         .unwrap();
 
-    let checkers = service_client
+    let registered = service_client
         .get_checkers()
         .recv(program_id)
         .await
         .unwrap();
-    println!("{:?}", checkers.len());
+    assert_eq!(registered, checkers);
 }
 
 #[tokio::test]
 async fn check_points_set() {
-    let system = System::new();
-    system.init_logger();
-    system.mint_to(ACTOR_ID, 1_000_000_000_000_000);
-
-    let remoting = GTestRemoting::new(system, ACTOR_ID.into());
-    remoting.system().init_logger();
-
-    // Submit program code into the system
-    let program_code_id = remoting.system().submit_code(manager::WASM_BINARY);
-    let checker_code_id = remoting
-        .system()
-        .submit_code(mandelbrot_checker::WASM_BINARY);
-
-    let checker_factory =
-        mandelbrot_checker_client::MandelbrotCheckerFactory::new(remoting.clone());
-
-    let mut checkers: Vec<ActorId> = Vec::new();
-
-    for i in 0..100 {
-        let program_id = checker_factory
-            .new()
-            .send_recv(checker_code_id, &[i])
-            .await
-            .unwrap();
-        checkers.push(program_id.into());
-    }
-    let program_factory = manager_client::ManagerFactory::new(remoting.clone());
-
-    let program_id = program_factory
-        .new()
-        .send_recv(program_code_id, b"salt")
-        .await
-        .unwrap();
+    let Setup {
+        remoting,
+        program_id,
+        checkers,
+        ..
+    } = setup(100).await;
 
     let mut service_client = manager_client::Manager::new(remoting.clone());
 
@@ -156,6 +188,7 @@ async fn check_points_set() {
         .add_checkers(checkers)
         .send_recv(program_id)
         .await
+        .unwrap()
         .unwrap();
 
     let checkers = service_client
@@ -166,31 +199,24 @@ async fn check_points_set() {
 
     assert_eq!(checkers.len(), 100);
 
-    let width = 600;
-    let height = 600;
+    let job_id = service_client
+        .create_job(job_params(600, 600, 1000))
+        .send_recv(program_id)
+        .await
+        .unwrap()
+        .unwrap();
 
     for _i in 0..12 {
         service_client
-            .generate_and_store_points(
-                width,
-                height,
-                FixedPoint { num: -2, scale: 0 },
-                FixedPoint { num: 1, scale: 0 },
-                FixedPoint { num: -15, scale: 2 },
-                FixedPoint { num: 15, scale: 1 },
-                30_000,
-                false,
-                false,
-                0,
-                0,
-            )
+            .generate_and_store_points(job_id, 30_000, false, false, 0)
             .send_recv(program_id)
             .await
+            .unwrap()
             .unwrap();
     }
 
     let points_len = service_client
-        .get_points_len()
+        .get_points_len(job_id)
         .recv(program_id)
         .await
         .unwrap();
@@ -200,22 +226,40 @@ async fn check_points_set() {
     for _i in 0..30 {
         println!("{:?}", _i);
         service_client
-            .check_points_set(1000, 10, false)
+            .check_points_set(10, false)
             .send_recv(program_id)
             .await
+            .unwrap()
             .unwrap();
         remoting.system().run_next_block();
     }
 
-    let msg_sent = service_client.points_sent().recv(program_id).await.unwrap();
-
-    assert_eq!(msg_sent, 30_000);
-
-    let point_results = service_client
-        .get_results(0, 30_000)
+    let msg_sent = service_client
+        .points_sent(job_id)
         .recv(program_id)
         .await
         .unwrap();
+
+    assert_eq!(msg_sent, 30_000);
+
+    // Fetch the results of the dispatched points page by page
+    let mut point_results = Vec::new();
+    let mut cursor = Some(0);
+    while let Some(start) = cursor.filter(|&start| start < msg_sent) {
+        let page = service_client
+            .get_results(job_id, start, msg_sent - start)
+            .recv(program_id)
+            .await
+            .unwrap()
+            .unwrap();
+        point_results.extend(page.results);
+        cursor = page.next_cursor;
+    }
+    assert_eq!(point_results.len(), 30_000);
+    assert!(point_results
+        .iter()
+        .enumerate()
+        .all(|(i, point)| point.index == i as u32));
     if point_results.iter().all(|point| point.checked) {
         println!("All points are checked!");
     } else {
@@ -226,3 +270,1828 @@ async fn check_points_set() {
         );
     }
 }
+
+#[tokio::test]
+async fn access_control() {
+    let Setup {
+        remoting,
+        program_id,
+        ..
+    } = setup(0).await;
+
+    let mut owner_client = manager_client::Manager::new(remoting.clone());
+    let mut user_client =
+        manager_client::Manager::new(remoting.clone().with_actor_id(USER_ID.into()));
+
+    let owner = owner_client.owner().recv(program_id).await.unwrap();
+    assert_eq!(owner, ACTOR_ID.into());
+
+    // A random account can neither register checkers nor wipe the results
+    let result = user_client
+        .add_checkers(vec![ActorId::from(100)])
+        .send_recv(program_id)
+        .await
+        .unwrap();
+    assert_eq!(result, Err(ManagerError::NotAdmin));
+
+    let result = user_client
+        .check_points_set(10, false)
+        .send_recv(program_id)
+        .await
+        .unwrap();
+    assert_eq!(result, Err(ManagerError::NotAdmin));
+
+    let result = user_client
+        .add_admin(USER_ID.into())
+        .send_recv(program_id)
+        .await
+        .unwrap();
+    assert_eq!(result, Err(ManagerError::NotOwner));
+
+    // Once promoted, the user can manage checkers
+    owner_client
+        .add_admin(USER_ID.into())
+        .send_recv(program_id)
+        .await
+        .unwrap()
+        .unwrap();
+
+    for checker in [100, 101] {
+        user_client
+            .add_checker_with_capabilities(ActorId::from(checker), mandelbrot_capabilities(10))
+            .send_recv(program_id)
+            .await
+            .unwrap()
+            .unwrap();
+    }
+
+    user_client
+        .remove_checkers(vec![ActorId::from(100)])
+        .send_recv(program_id)
+        .await
+        .unwrap()
+        .unwrap();
+
+    let checkers = user_client.get_checkers().recv(program_id).await.unwrap();
+    assert_eq!(checkers, vec![ActorId::from(101)]);
+
+    // After the transfer only the new owner can manage admins
+    owner_client
+        .transfer_ownership(USER_ID.into())
+        .send_recv(program_id)
+        .await
+        .unwrap()
+        .unwrap();
+
+    let result = owner_client
+        .remove_admin(USER_ID.into())
+        .send_recv(program_id)
+        .await
+        .unwrap();
+    assert_eq!(result, Err(ManagerError::NotOwner));
+
+    let owner = owner_client.owner().recv(program_id).await.unwrap();
+    assert_eq!(owner, USER_ID.into());
+}
+
+#[tokio::test]
+async fn checker_stats() {
+    let Setup {
+        remoting,
+        program_id,
+        checkers,
+        ..
+    } = setup(3).await;
+
+    let mut service_client = manager_client::Manager::new(remoting.clone());
+
+    service_client
+        .add_checkers(checkers[..2].to_vec())
+        .send_recv(program_id)
+        .await
+        .unwrap()
+        .unwrap();
+
+    let job_id = service_client
+        .create_job(job_params(20, 20, 100))
+        .send_recv(program_id)
+        .await
+        .unwrap()
+        .unwrap();
+
+    service_client
+        .generate_and_store_points(job_id, 400, false, false, 0)
+        .send_recv(program_id)
+        .await
+        .unwrap()
+        .unwrap();
+
+    service_client
+        .check_points_set(50, false)
+        .send_recv(program_id)
+        .await
+        .unwrap()
+        .unwrap();
+    remoting.system().run_next_block();
+    remoting.system().run_next_block();
+
+    for checker in &checkers[..2] {
+        let stats = service_client
+            .get_checker_stats(*checker)
+            .recv(program_id)
+            .await
+            .unwrap()
+            .expect("Checker is registered");
+        assert_eq!(stats.batches_completed, 1);
+        assert_eq!(stats.points_processed, 50);
+        assert_eq!(stats.failures, 0);
+        assert_eq!(stats.pending_batches, 0);
+    }
+
+    // A checker can be swapped for another one, which starts with fresh statistics
+    service_client
+        .replace_checker(checkers[0], checkers[2])
+        .send_recv(program_id)
+        .await
+        .unwrap()
+        .unwrap();
+
+    let result = service_client
+        .replace_checker(checkers[0], checkers[2])
+        .send_recv(program_id)
+        .await
+        .unwrap();
+    assert_eq!(result, Err(ManagerError::CheckerNotFound));
+
+    let registered = service_client
+        .get_checkers()
+        .recv(program_id)
+        .await
+        .unwrap();
+    assert_eq!(registered, vec![checkers[2], checkers[1]]);
+
+    let stats = service_client
+        .get_checker_stats(checkers[2])
+        .recv(program_id)
+        .await
+        .unwrap()
+        .expect("Checker is registered");
+    assert_eq!(stats.batches_completed, 0);
+    assert_eq!(stats.points_processed, 0);
+
+    service_client
+        .remove_checkers(vec![checkers[1]])
+        .send_recv(program_id)
+        .await
+        .unwrap()
+        .unwrap();
+
+    let stats = service_client
+        .get_checker_stats(checkers[1])
+        .recv(program_id)
+        .await
+        .unwrap();
+    assert_eq!(stats, None);
+}
+
+#[tokio::test]
+async fn concurrent_jobs() {
+    let Setup {
+        remoting,
+        program_id,
+        checkers,
+        ..
+    } = setup(4).await;
+
+    let mut owner_client = manager_client::Manager::new(remoting.clone());
+    let mut user_client =
+        manager_client::Manager::new(remoting.clone().with_actor_id(USER_ID.into()));
+
+    owner_client
+        .add_checkers(checkers)
+        .send_recv(program_id)
+        .await
+        .unwrap()
+        .unwrap();
+
+    // Two users create jobs with different parameters
+    let owner_job = owner_client
+        .create_job(job_params(10, 10, 50))
+        .send_recv(program_id)
+        .await
+        .unwrap()
+        .unwrap();
+
+    let user_job = user_client
+        .create_job(job_params(20, 10, 100))
+        .send_recv(program_id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_ne!(owner_job, user_job);
+
+    // An empty image, more points than `MAX_JOB_POINTS` and a `max_iter` above `MAX_ITER`
+    for params in [
+        job_params(0, 10, 100),
+        job_params(1001, 1000, 100),
+        job_params(10, 10, 100_001),
+    ] {
+        let result = user_client
+            .create_job(params)
+            .send_recv(program_id)
+            .await
+            .unwrap();
+        assert_eq!(result, Err(ManagerError::InvalidJobParams));
+    }
+
+    // Only the creator or an admin can touch a job
+    let result = user_client
+        .restart_job(owner_job)
+        .send_recv(program_id)
+        .await
+        .unwrap();
+    assert_eq!(result, Err(ManagerError::NotJobCreator));
+
+    owner_client
+        .generate_and_store_points(owner_job, 100, false, false, 0)
+        .send_recv(program_id)
+        .await
+        .unwrap()
+        .unwrap();
+
+    user_client
+        .generate_and_store_points(user_job, 200, false, false, 0)
+        .send_recv(program_id)
+        .await
+        .unwrap()
+        .unwrap();
+
+    // Both jobs are served by the shared checker pool
+    for _i in 0..10 {
+        owner_client
+            .check_points_set(25, false)
+            .send_recv(program_id)
+            .await
+            .unwrap()
+            .unwrap();
+        remoting.system().run_next_block();
+    }
+    remoting.system().run_next_block();
+
+    for (job_id, total_points) in [(owner_job, 100), (user_job, 200)] {
+        let job = owner_client
+            .get_job(job_id)
+            .recv(program_id)
+            .await
+            .unwrap()
+            .expect("Job exists");
+        assert_eq!(job.total_points, total_points);
+        assert_eq!(job.points_checked, total_points);
+        assert_eq!(job.status, JobStatus::Completed);
+    }
+
+    let user_job_info = user_client
+        .get_job(user_job)
+        .recv(program_id)
+        .await
+        .unwrap()
+        .expect("Job exists");
+    assert_eq!(user_job_info.creator, USER_ID.into());
+    assert_eq!(user_job_info.params.max_iter, 100);
+
+    // Renderers size the image from the parameters of the job
+    let params = user_client
+        .get_job_params(user_job)
+        .recv(program_id)
+        .await
+        .unwrap()
+        .expect("Job exists");
+    assert_eq!((params.width, params.height), (20, 10));
+    assert_eq!(params.max_iter, 100);
+
+    user_client
+        .remove_job(user_job)
+        .send_recv(program_id)
+        .await
+        .unwrap()
+        .unwrap();
+
+    let jobs = owner_client.get_jobs().recv(program_id).await.unwrap();
+    assert_eq!(jobs.len(), 1);
+    assert_eq!(jobs[0].id, owner_job);
+
+    let params = owner_client
+        .get_job_params(user_job)
+        .recv(program_id)
+        .await
+        .unwrap();
+    assert!(params.is_none());
+}
+
+#[tokio::test]
+async fn job_limits() {
+    let Setup {
+        remoting,
+        program_id,
+        ..
+    } = setup(0).await;
+
+    let mut user_client =
+        manager_client::Manager::new(remoting.clone().with_actor_id(USER_ID.into()));
+
+    let mut user_jobs = Vec::new();
+    for _i in 0..MAX_JOBS_PER_CREATOR {
+        let job_id = user_client
+            .create_job(job_params(10, 10, 50))
+            .send_recv(program_id)
+            .await
+            .unwrap()
+            .unwrap();
+        user_jobs.push(job_id);
+    }
+    let result = user_client
+        .create_job(job_params(10, 10, 50))
+        .send_recv(program_id)
+        .await
+        .unwrap();
+    assert_eq!(result, Err(ManagerError::TooManyJobs));
+
+    // Removing a job frees its slot
+    user_client
+        .remove_job(user_jobs[0])
+        .send_recv(program_id)
+        .await
+        .unwrap()
+        .unwrap();
+    user_client
+        .create_job(job_params(10, 10, 50))
+        .send_recv(program_id)
+        .await
+        .unwrap()
+        .unwrap();
+
+    // Other accounts fill the manager with the largest jobs, up to what the
+    // user jobs leave
+    let user_points = MAX_JOBS_PER_CREATOR * 100;
+    let mut free_points = MAX_LIVE_POINTS - user_points;
+    let mut last_job = None;
+    for account in 100..104u64 {
+        remoting.system().mint_to(account, 1_000_000_000_000_000);
+        let mut client =
+            manager_client::Manager::new(remoting.clone().with_actor_id(account.into()));
+        for _i in 0..MAX_JOBS_PER_CREATOR {
+            let height = (free_points / 1000).min(1000);
+            if height == 0 {
+                break;
+            }
+            let job_id = client
+                .create_job(job_params(1000, height, 50))
+                .send_recv(program_id)
+                .await
+                .unwrap()
+                .unwrap();
+            free_points -= 1000 * height;
+            last_job = Some((account, job_id));
+        }
+    }
+    assert!(free_points < 1000);
+
+    let mut owner_client = manager_client::Manager::new(remoting.clone());
+    let result = owner_client
+        .create_job(job_params(40, 25, 50))
+        .send_recv(program_id)
+        .await
+        .unwrap();
+    assert_eq!(result, Err(ManagerError::TooManyPoints));
+
+    // Removed jobs stop counting
+    let (account, job_id) = last_job.expect("Accounts created jobs");
+    manager_client::Manager::new(remoting.clone().with_actor_id(account.into()))
+        .remove_job(job_id)
+        .send_recv(program_id)
+        .await
+        .unwrap()
+        .unwrap();
+    owner_client
+        .create_job(job_params(40, 25, 50))
+        .send_recv(program_id)
+        .await
+        .unwrap()
+        .unwrap();
+}
+
+#[tokio::test]
+async fn non_square_layout() {
+    let Setup {
+        remoting,
+        program_id,
+        ..
+    } = setup(0).await;
+
+    let mut service_client = manager_client::Manager::new(remoting.clone());
+
+    // 8x6 pixels over [-2, 2] x [-1.5, 1.5]: the step is 0.5 on both axes
+    let params = JobParams {
+        fractal: Fractal::Mandelbrot,
+        width: 8,
+        height: 6,
+        x_min: FixedPoint { num: -2, scale: 0 },
+        x_max: FixedPoint { num: 2, scale: 0 },
+        y_min: FixedPoint { num: -15, scale: 1 },
+        y_max: FixedPoint { num: 15, scale: 1 },
+        max_iter: 100,
+        smooth: false,
+        deep_zoom: None,
+        adaptive: false,
+    };
+    let job_id = service_client
+        .create_job(params)
+        .send_recv(program_id)
+        .await
+        .unwrap()
+        .unwrap();
+
+    service_client
+        .generate_and_store_points(job_id, 48, false, false, 0)
+        .send_recv(program_id)
+        .await
+        .unwrap()
+        .unwrap();
+
+    // Pages are ordered by index and chained through the cursor
+    let mut results = Vec::new();
+    let mut cursors = Vec::new();
+    let mut cursor = Some(0);
+    while let Some(start) = cursor {
+        let page = service_client
+            .get_results(job_id, start, 20)
+            .recv(program_id)
+            .await
+            .unwrap()
+            .unwrap();
+        results.extend(page.results);
+        cursors.push(page.next_cursor);
+        cursor = page.next_cursor;
+    }
+    assert_eq!(cursors, vec![Some(20), Some(40), None]);
+    assert_eq!(results.len(), 48);
+    assert!(results
+        .iter()
+        .enumerate()
+        .all(|(i, point)| point.index == i as u32));
+
+    // A zero limit still reads one result, so the cursor moves forward
+    let page = service_client
+        .get_results(job_id, 47, 0)
+        .recv(program_id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(page.results.len(), 1);
+    assert_eq!(page.next_cursor, None);
+    let page = service_client
+        .get_results(job_id, 0, 0)
+        .recv(program_id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(page.results.len(), 1);
+    assert_eq!(page.next_cursor, Some(1));
+
+    let result = service_client
+        .get_results(job_id + 1, 0, 20)
+        .recv(program_id)
+        .await
+        .unwrap();
+    assert!(matches!(result, Err(ManagerError::JobNotFound)));
+
+    // Pixel `index` is in column `index % width` and row `index / width`, row 0 is the top
+    let expected = [
+        (0, (-2, 0), (15, 1)),
+        (7, (15, 1), (15, 1)),
+        (8, (-2, 0), (1, 0)),
+        (19, (-1, 0), (5, 1)),
+        (47, (15, 1), (-1, 0)),
+    ];
+    for (index, (re_num, re_scale), (im_num, im_scale)) in expected {
+        let point = &results[index];
+        assert_eq!(
+            point.c_re,
+            FixedPoint {
+                num: re_num,
+                scale: re_scale
+            }
+        );
+        assert_eq!(
+            point.c_im,
+            FixedPoint {
+                num: im_num,
+                scale: im_scale
+            }
+        );
+    }
+}
+
+#[tokio::test]
+async fn progress_events() {
+    let Setup {
+        remoting,
+        program_id,
+        checkers,
+        ..
+    } = setup(2).await;
+
+    let mut service_client = manager_client::Manager::new(remoting.clone());
+    let mut listener = manager_client::manager::events::listener(remoting.clone());
+    let mut events = listener.listen().await.unwrap();
+
+    service_client
+        .add_checkers(checkers)
+        .send_recv(program_id)
+        .await
+        .unwrap()
+        .unwrap();
+
+    let job_id = service_client
+        .create_job(job_params(10, 10, 100))
+        .send_recv(program_id)
+        .await
+        .unwrap()
+        .unwrap();
+
+    // Generation and checking continue on their own after one message
+    service_client
+        .generate_and_store_points(job_id, 50, true, true, 10)
+        .send_recv(program_id)
+        .await
+        .unwrap()
+        .unwrap();
+
+    let mut generated = Vec::new();
+    let mut dispatched = 0;
+    let mut completed = 0;
+    let mut render_completed = None;
+    for _i in 0..20 {
+        while let Some(Some((source, event))) = events.next().now_or_never() {
+            assert_eq!(source, program_id);
+            match event {
+                ManagerEvents::PointsGenerated { total, .. } => generated.push(total),
+                ManagerEvents::BatchDispatched { count, .. } => dispatched += count,
+                ManagerEvents::BatchCompleted { count, .. } => completed += count,
+                ManagerEvents::RenderCompleted {
+                    job_id: completed_job,
+                    duration_blocks,
+                } => render_completed = Some((completed_job, duration_blocks)),
+                _ => {}
+            }
+        }
+        if render_completed.is_some() {
+            break;
+        }
+        remoting.system().run_next_block();
+    }
+
+    assert_eq!(generated, vec![50, 100]);
+    assert_eq!(dispatched, 100);
+    assert_eq!(completed, 100);
+    let (completed_job, duration_blocks) = render_completed.expect("Render is completed");
+    assert_eq!(completed_job, job_id);
+
+    let job = service_client
+        .get_job(job_id)
+        .recv(program_id)
+        .await
+        .unwrap()
+        .expect("Job exists");
+    assert_eq!(job.status, JobStatus::Completed);
+    assert_eq!(
+        job.completed_at.unwrap() - job.started_at.unwrap(),
+        duration_blocks
+    );
+}
+
+#[tokio::test]
+async fn smooth_results() {
+    let Setup {
+        remoting,
+        program_id,
+        checkers,
+        ..
+    } = setup(2).await;
+
+    let mut service_client = manager_client::Manager::new(remoting.clone());
+
+    service_client
+        .add_checkers(checkers)
+        .send_recv(program_id)
+        .await
+        .unwrap()
+        .unwrap();
+
+    let mut smooth_job_ids = Vec::new();
+    for smooth in [true, false] {
+        let job_id = service_client
+            .create_job(JobParams {
+                smooth,
+                ..job_params(10, 10, 100)
+            })
+            .send_recv(program_id)
+            .await
+            .unwrap()
+            .unwrap();
+        smooth_job_ids.push((job_id, smooth));
+
+        service_client
+            .generate_and_store_points(job_id, 100, false, true, 10)
+            .send_recv(program_id)
+            .await
+            .unwrap()
+            .unwrap();
+    }
+
+    for _i in 0..20 {
+        remoting.system().run_next_block();
+    }
+
+    for (job_id, smooth) in smooth_job_ids {
+        let job = service_client
+            .get_job(job_id)
+            .recv(program_id)
+            .await
+            .unwrap()
+            .expect("Job exists");
+        assert_eq!(job.status, JobStatus::Completed);
+
+        let page = service_client
+            .get_results(job_id, 0, 100)
+            .recv(program_id)
+            .await
+            .unwrap()
+            .unwrap();
+        for point in page.results {
+            assert!(point.checked);
+            let Some(smooth_iter) = point.smooth else {
+                assert!(!smooth);
+                continue;
+            };
+            assert!(smooth);
+            let smooth_iter = smooth_iter.num as f64 / 10f64.powi(smooth_iter.scale as i32);
+            if point.iter == 100 {
+                assert_eq!(smooth_iter, 100.0);
+            } else {
+                assert!((smooth_iter - point.iter as f64).abs() < 3.0);
+            }
+        }
+    }
+}
+
+#[tokio::test]
+async fn fractal_jobs() {
+    let Setup {
+        remoting,
+        program_id,
+        checkers,
+        ..
+    } = setup(1).await;
+
+    let mut service_client = manager_client::Manager::new(remoting.clone());
+
+    service_client
+        .add_checkers(checkers)
+        .send_recv(program_id)
+        .await
+        .unwrap()
+        .unwrap();
+
+    for fractal in [
+        Fractal::Multibrot { power: 1 },
+        Fractal::Julia {
+            c_re: FixedPoint { num: 2, scale: 0 },
+            c_im: FixedPoint { num: 2, scale: 0 },
+        },
+    ] {
+        let result = service_client
+            .create_job(JobParams {
+                fractal,
+                ..job_params(8, 6, 100)
+            })
+            .send_recv(program_id)
+            .await
+            .unwrap();
+        assert!(matches!(result, Err(ManagerError::InvalidJobParams)));
+    }
+
+    // The Julia set of 0 is the unit disc. 8x6 pixels over [-2, 2] x [-1.5, 1.5]
+    let params = JobParams {
+        fractal: Fractal::Julia {
+            c_re: FixedPoint { num: 0, scale: 0 },
+            c_im: FixedPoint { num: 0, scale: 0 },
+        },
+        x_max: FixedPoint { num: 2, scale: 0 },
+        ..job_params(8, 6, 100)
+    };
+    let job_id = service_client
+        .create_job(params)
+        .send_recv(program_id)
+        .await
+        .unwrap()
+        .unwrap();
+
+    service_client
+        .generate_and_store_points(job_id, 48, false, true, 12)
+        .send_recv(program_id)
+        .await
+        .unwrap()
+        .unwrap();
+
+    for _i in 0..10 {
+        remoting.system().run_next_block();
+    }
+
+    let page = service_client
+        .get_results(job_id, 0, 48)
+        .recv(program_id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(page.results.len(), 48);
+    for point in page.results {
+        assert!(point.checked);
+        // Coordinates are multiples of 0.5
+        let re = point.c_re.num * 10 / 10i64.pow(point.c_re.scale);
+        let im = point.c_im.num * 10 / 10i64.pow(point.c_im.scale);
+        assert_eq!(
+            point.iter == 100,
+            re * re + im * im <= 100,
+            "point {}",
+            point.index
+        );
+    }
+}
+
+#[tokio::test]
+async fn deep_zoom_jobs() {
+    let Setup {
+        remoting,
+        program_id,
+        checkers,
+        ..
+    } = setup(1).await;
+
+    let mut service_client = manager_client::Manager::new(remoting.clone());
+
+    service_client
+        .add_checkers(checkers)
+        .send_recv(program_id)
+        .await
+        .unwrap()
+        .unwrap();
+
+    // `num` is little-endian two's complement
+    let number = |num: i8, scale| BigFixedPoint {
+        num: vec![num as u8],
+        scale,
+    };
+    let deep_zoom = |center_re| DeepZoom {
+        center_re,
+        center_im: number(0, 0),
+        offset_scale: 0,
+    };
+
+    // Deep zooms are Mandelbrot only, around a center inside the disc of radius 2
+    for params in [
+        JobParams {
+            fractal: Fractal::BurningShip,
+            deep_zoom: Some(deep_zoom(number(-1, 0))),
+            ..job_params(8, 6, 100)
+        },
+        JobParams {
+            deep_zoom: Some(deep_zoom(number(21, 1))),
+            ..job_params(8, 6, 100)
+        },
+        // The reference orbit of `max_iter` above `MAX_DEEP_ZOOM_ITER` is too long
+        JobParams {
+            deep_zoom: Some(deep_zoom(number(-1, 0))),
+            ..job_params(8, 6, 10_001)
+        },
+    ] {
+        let result = service_client
+            .create_job(params)
+            .send_recv(program_id)
+            .await
+            .unwrap();
+        assert!(matches!(result, Err(ManagerError::InvalidJobParams)));
+    }
+
+    // The same 8x6 pixels over [-3, 1] x [-1.5, 1.5], the second job as offsets from -1
+    let direct = JobParams {
+        x_min: FixedPoint { num: -3, scale: 0 },
+        ..job_params(8, 6, 100)
+    };
+    let perturbed = JobParams {
+        x_max: FixedPoint { num: 2, scale: 0 },
+        deep_zoom: Some(deep_zoom(number(-1, 0))),
+        ..job_params(8, 6, 100)
+    };
+    let mut job_ids = Vec::new();
+    for params in [direct, perturbed] {
+        let job_id = service_client
+            .create_job(params)
+            .send_recv(program_id)
+            .await
+            .unwrap()
+            .unwrap();
+        service_client
+            .generate_and_store_points(job_id, 48, false, true, 12)
+            .send_recv(program_id)
+            .await
+            .unwrap()
+            .unwrap();
+        job_ids.push(job_id);
+    }
+
+    for _i in 0..20 {
+        remoting.system().run_next_block();
+    }
+
+    let mut pages = Vec::new();
+    for job_id in job_ids {
+        let page = service_client
+            .get_results(job_id, 0, 48)
+            .recv(program_id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(page.results.len(), 48);
+        assert!(page.results.iter().all(|point| point.checked));
+        pages.push(page);
+    }
+    for (direct, perturbed) in pages[0].results.iter().zip(&pages[1].results) {
+        assert_eq!(direct.iter, perturbed.iter, "point {}", direct.index);
+    }
+}
+
+#[tokio::test]
+async fn adaptive_jobs() {
+    let Setup {
+        remoting,
+        program_id,
+        checkers,
+        ..
+    } = setup(4).await;
+
+    let mut service_client = manager_client::Manager::new(remoting.clone());
+
+    service_client
+        .add_checkers(checkers)
+        .send_recv(program_id)
+        .await
+        .unwrap()
+        .unwrap();
+
+    // The same image computed point by point and adaptively
+    let mut job_ids = Vec::new();
+    for adaptive in [false, true] {
+        let job_id = service_client
+            .create_job(JobParams {
+                adaptive,
+                ..job_params(64, 48, 100)
+            })
+            .send_recv(program_id)
+            .await
+            .unwrap()
+            .unwrap();
+        service_client
+            .generate_and_store_points(job_id, 3072, false, true, 100)
+            .send_recv(program_id)
+            .await
+            .unwrap()
+            .unwrap();
+        job_ids.push(job_id);
+    }
+
+    for _i in 0..60 {
+        remoting.system().run_next_block();
+    }
+
+    let total = 64 * 48;
+    let mut pages = Vec::new();
+    for job_id in job_ids.iter().copied() {
+        let info = service_client
+            .get_job(job_id)
+            .recv(program_id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(info.status, JobStatus::Completed);
+        assert_eq!(info.points_checked, total);
+
+        let page = service_client
+            .get_results(job_id, 0, total)
+            .recv(program_id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(page.results.iter().all(|point| point.checked));
+        pages.push(page);
+    }
+
+    // Only the points near the boundaries between iteration counts are sent
+    let info = service_client
+        .get_job(job_ids[1])
+        .recv(program_id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(info.points_filled > 0);
+    assert!(info.points_sent < total * 2 / 3);
+    assert!(info.points_sent + info.points_filled >= total);
+
+    // Details smaller than a tile can be filled over
+    let mismatches = pages[0]
+        .results
+        .iter()
+        .zip(&pages[1].results)
+        .filter(|(direct, adaptive)| direct.iter != adaptive.iter)
+        .count() as u32;
+    assert!(mismatches <= total / 100, "{mismatches} mismatches");
+}
+
+#[tokio::test]
+async fn verification_and_disputes() {
+    let Setup {
+        remoting,
+        program_id,
+        checkers: honest,
+        ..
+    } = setup(2).await;
+
+    let mut service_client = manager_client::Manager::new(remoting.clone());
+
+    // The test account acts as a checker that makes its results up
+    let liar: ActorId = ACTOR_ID.into();
+    service_client
+        .add_checker_with_capabilities(liar, mandelbrot_capabilities(100))
+        .send_recv(program_id)
+        .await
+        .unwrap()
+        .unwrap();
+    service_client
+        .add_checkers(honest.clone())
+        .send_recv(program_id)
+        .await
+        .unwrap()
+        .unwrap();
+    service_client
+        .set_verification(VerificationConfig {
+            sample_size: 4,
+            max_disputes: 3,
+        })
+        .send_recv(program_id)
+        .await
+        .unwrap()
+        .unwrap();
+
+    let mut job_ids = Vec::new();
+    for _ in 0..2 {
+        let job_id = service_client
+            .create_job(job_params(4, 2, 100))
+            .send_recv(program_id)
+            .await
+            .unwrap()
+            .unwrap();
+        service_client
+            .generate_and_store_points(job_id, 8, false, false, 0)
+            .send_recv(program_id)
+            .await
+            .unwrap()
+            .unwrap();
+        job_ids.push(job_id);
+    }
+
+    // Honest checkers agree on the sample
+    service_client
+        .send_next_batch(honest[0], 8)
+        .send_recv(program_id)
+        .await
+        .unwrap()
+        .unwrap();
+    for _i in 0..5 {
+        remoting.system().run_next_block();
+    }
+    assert!(service_client
+        .get_disputes(job_ids[0])
+        .recv(program_id)
+        .await
+        .unwrap()
+        .is_empty());
+    let stats = service_client
+        .get_checker_stats(honest[1])
+        .recv(program_id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(stats.points_processed, 4);
+
+    // The second job is sent to the liar, in the third batch after the
+    // honest one and its sample
+    service_client
+        .send_next_batch(liar, 8)
+        .send_recv(program_id)
+        .await
+        .unwrap()
+        .unwrap();
+    service_client
+        .result_calculated(2, (0..8).collect(), vec![1000; 8], vec![])
+        .send_recv(program_id)
+        .await
+        .unwrap()
+        .unwrap();
+    for _i in 0..5 {
+        remoting.system().run_next_block();
+    }
+
+    let disputes = service_client
+        .get_disputes(job_ids[1])
+        .recv(program_id)
+        .await
+        .unwrap();
+    assert_eq!(disputes.len(), 4);
+    let page = service_client
+        .get_results(job_ids[1], 0, 8)
+        .recv(program_id)
+        .await
+        .unwrap()
+        .unwrap();
+    for dispute in disputes {
+        assert_eq!(dispute.checker, liar);
+        assert_eq!(dispute.checker_iter, 1000);
+        assert_eq!(dispute.verifier, honest[0]);
+        assert_eq!(dispute.status, DisputeStatus::Resolved { faulty: liar });
+        // The result of the verifier replaces the made up one
+        assert_eq!(
+            page.results[dispute.index as usize].iter,
+            dispute.verifier_iter
+        );
+    }
+    let job = service_client
+        .get_job(job_ids[1])
+        .recv(program_id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(job.results_version, 4);
+
+    // The liar lost its third dispute and was removed
+    let checkers = service_client
+        .get_checkers()
+        .recv(program_id)
+        .await
+        .unwrap();
+    assert_eq!(checkers, honest);
+}
+
+#[tokio::test]
+async fn lost_disputes_correct_smooth_counts() {
+    let Setup {
+        remoting,
+        program_id,
+        checkers: honest,
+        ..
+    } = setup(2).await;
+
+    let mut service_client = manager_client::Manager::new(remoting.clone());
+
+    let liar: ActorId = ACTOR_ID.into();
+    service_client
+        .add_checker_with_capabilities(liar, mandelbrot_capabilities(100))
+        .send_recv(program_id)
+        .await
+        .unwrap()
+        .unwrap();
+    service_client
+        .add_checkers(honest)
+        .send_recv(program_id)
+        .await
+        .unwrap()
+        .unwrap();
+    service_client
+        .set_verification(VerificationConfig {
+            sample_size: 8,
+            max_disputes: 0,
+        })
+        .send_recv(program_id)
+        .await
+        .unwrap()
+        .unwrap();
+
+    let job_id = service_client
+        .create_job(JobParams {
+            smooth: true,
+            ..job_params(4, 2, 100)
+        })
+        .send_recv(program_id)
+        .await
+        .unwrap()
+        .unwrap();
+    service_client
+        .generate_and_store_points(job_id, 8, false, false, 0)
+        .send_recv(program_id)
+        .await
+        .unwrap()
+        .unwrap();
+
+    service_client
+        .send_next_batch(liar, 8)
+        .send_recv(program_id)
+        .await
+        .unwrap()
+        .unwrap();
+    // Smooth counts are stored at `SMOOTH_SCALE`, a reply at another scale is
+    // refused and its points go back to the queue
+    let result = service_client
+        .result_calculated(
+            0,
+            (0..8).collect(),
+            vec![1000; 8],
+            vec![FixedPoint { num: 1, scale: 0 }; 8],
+        )
+        .send_recv(program_id)
+        .await
+        .unwrap();
+    assert!(matches!(result, Err(ManagerError::InvalidResults)));
+
+    // The liar makes up both the counts and the smooth counts
+    let made_up = FixedPoint {
+        num: -1,
+        scale: SMOOTH_SCALE,
+    };
+    service_client
+        .send_next_batch(liar, 8)
+        .send_recv(program_id)
+        .await
+        .unwrap()
+        .unwrap();
+    service_client
+        .result_calculated(1, (0..8).collect(), vec![1000; 8], vec![made_up.clone(); 8])
+        .send_recv(program_id)
+        .await
+        .unwrap()
+        .unwrap();
+    for _i in 0..5 {
+        remoting.system().run_next_block();
+    }
+
+    let disputes = service_client
+        .get_disputes(job_id)
+        .recv(program_id)
+        .await
+        .unwrap();
+    assert_eq!(disputes.len(), 8);
+    let page = service_client
+        .get_results(job_id, 0, 8)
+        .recv(program_id)
+        .await
+        .unwrap()
+        .unwrap();
+    for dispute in disputes {
+        assert_eq!(dispute.status, DisputeStatus::Resolved { faulty: liar });
+        let result = &page.results[dispute.index as usize];
+        assert_eq!(result.iter, dispute.verifier_iter);
+        // The smooth count of the arbiter replaces the made up one
+        assert!(result.smooth.is_some());
+        assert_ne!(result.smooth, Some(made_up.clone()));
+    }
+}
+
+#[tokio::test]
+async fn rewards_and_withdrawals() {
+    let Setup {
+        remoting,
+        program_id,
+        checkers,
+        ..
+    } = setup(2).await;
+
+    let mut service_client = manager_client::Manager::new(remoting.clone());
+    let mut user_client =
+        manager_client::Manager::new(remoting.clone().with_actor_id(USER_ID.into()));
+
+    service_client
+        .add_checkers(checkers.clone())
+        .send_recv(program_id)
+        .await
+        .unwrap()
+        .unwrap();
+    let payee = service_client
+        .get_payee(checkers[0])
+        .recv(program_id)
+        .await
+        .unwrap();
+    assert_eq!(payee, Some(ACTOR_ID.into()));
+
+    // 8 points funded with 8 * 10^13
+    let reward_per_point = 10_000_000_000_000;
+    let job_id = service_client
+        .create_job(job_params(4, 2, 100))
+        .with_value(8 * reward_per_point)
+        .send_recv(program_id)
+        .await
+        .unwrap()
+        .unwrap();
+    let info = service_client
+        .get_job(job_id)
+        .recv(program_id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(info.budget, 8 * reward_per_point);
+    assert_eq!(info.reward_per_point, reward_per_point);
+
+    service_client
+        .generate_and_store_points(job_id, 8, false, true, 4)
+        .send_recv(program_id)
+        .await
+        .unwrap()
+        .unwrap();
+    for _i in 0..5 {
+        remoting.system().run_next_block();
+    }
+
+    for checker in checkers.iter().copied() {
+        let balance = service_client
+            .get_balance(checker)
+            .recv(program_id)
+            .await
+            .unwrap();
+        assert_eq!(balance, 4 * reward_per_point);
+    }
+    let info = service_client
+        .get_job(job_id)
+        .recv(program_id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(info.budget, 0);
+
+    // Only the payee withdraws
+    let result = user_client
+        .withdraw(checkers[0])
+        .send_recv(program_id)
+        .await
+        .unwrap();
+    assert_eq!(result, Err(ManagerError::NotPayee));
+
+    let result = service_client
+        .withdraw(checkers[0])
+        .send_recv(program_id)
+        .await
+        .unwrap();
+    assert_eq!(result, Ok(4 * reward_per_point));
+    let result = service_client
+        .withdraw(checkers[0])
+        .send_recv(program_id)
+        .await
+        .unwrap();
+    assert_eq!(result, Err(ManagerError::NothingToWithdraw));
+
+    // The payee can hand the rewards over
+    service_client
+        .set_payee(checkers[1], USER_ID.into())
+        .send_recv(program_id)
+        .await
+        .unwrap()
+        .unwrap();
+    let result = user_client
+        .withdraw(checkers[1])
+        .send_recv(program_id)
+        .await
+        .unwrap();
+    assert_eq!(result, Ok(4 * reward_per_point));
+    let balance = service_client
+        .get_balance(checkers[1])
+        .recv(program_id)
+        .await
+        .unwrap();
+    assert_eq!(balance, 0);
+
+    // The budget of a removed job goes back to its creator
+    let job_id = user_client
+        .create_job(job_params(4, 2, 100))
+        .with_value(8 * reward_per_point)
+        .send_recv(program_id)
+        .await
+        .unwrap()
+        .unwrap();
+    let result = user_client
+        .remove_job(job_id)
+        .send_recv(program_id)
+        .await
+        .unwrap();
+    assert_eq!(result, Ok(()));
+}
+
+#[tokio::test]
+async fn rewards_after_verification_and_restart() {
+    let Setup {
+        remoting,
+        program_id,
+        checkers,
+        ..
+    } = setup(2).await;
+
+    let mut service_client = manager_client::Manager::new(remoting.clone());
+
+    service_client
+        .add_checkers(checkers.clone())
+        .send_recv(program_id)
+        .await
+        .unwrap()
+        .unwrap();
+    service_client
+        .set_verification(VerificationConfig {
+            sample_size: 2,
+            max_disputes: 0,
+        })
+        .send_recv(program_id)
+        .await
+        .unwrap()
+        .unwrap();
+
+    let reward_per_point = 10_000_000_000_000;
+    let job_id = service_client
+        .create_job(job_params(4, 2, 100))
+        .with_value(8 * reward_per_point)
+        .send_recv(program_id)
+        .await
+        .unwrap()
+        .unwrap();
+    service_client
+        .generate_and_store_points(job_id, 8, false, false, 0)
+        .send_recv(program_id)
+        .await
+        .unwrap()
+        .unwrap();
+
+    // The first checker computes half of the points and the second one
+    // verifies a sample of them without taking from the budget
+    service_client
+        .send_next_batch(checkers[0], 4)
+        .send_recv(program_id)
+        .await
+        .unwrap()
+        .unwrap();
+    for _i in 0..5 {
+        remoting.system().run_next_block();
+    }
+    let stats = service_client
+        .get_checker_stats(checkers[1])
+        .recv(program_id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(stats.points_processed, 2);
+
+    for (checker, expected) in checkers.iter().copied().zip([4 * reward_per_point, 0]) {
+        let balance = service_client
+            .get_balance(checker)
+            .recv(program_id)
+            .await
+            .unwrap();
+        assert_eq!(balance, expected);
+    }
+    let info = service_client
+        .get_job(job_id)
+        .recv(program_id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(info.budget, 4 * reward_per_point);
+
+    // A restart splits the rest of the budget over all the points again
+    service_client
+        .restart_job(job_id)
+        .send_recv(program_id)
+        .await
+        .unwrap()
+        .unwrap();
+    let info = service_client
+        .get_job(job_id)
+        .recv(program_id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(info.budget, 4 * reward_per_point);
+    assert_eq!(info.reward_per_point, reward_per_point / 2);
+    // Clients keeping the checked results know they are gone
+    assert_eq!(info.results_version, 1);
+
+    service_client
+        .generate_and_store_points(job_id, 8, false, true, 4)
+        .send_recv(program_id)
+        .await
+        .unwrap()
+        .unwrap();
+    for _i in 0..10 {
+        remoting.system().run_next_block();
+    }
+
+    // Each checker computed 4 points at the new rate, the samples are unpaid
+    for (checker, expected) in checkers
+        .iter()
+        .copied()
+        .zip([6 * reward_per_point, 2 * reward_per_point])
+    {
+        let balance = service_client
+            .get_balance(checker)
+            .recv(program_id)
+            .await
+            .unwrap();
+        assert_eq!(balance, expected);
+    }
+    let info = service_client
+        .get_job(job_id)
+        .recv(program_id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(info.status, JobStatus::Completed);
+    assert_eq!(info.budget, 0);
+}
+
+#[tokio::test]
+async fn spawn_and_scale_checkers() {
+    let Setup {
+        remoting,
+        program_id,
+        checker_code_id,
+        ..
+    } = setup(0).await;
+
+    let mut service_client = manager_client::Manager::new(remoting.clone());
+    let mut user_client =
+        manager_client::Manager::new(remoting.clone().with_actor_id(USER_ID.into()));
+
+    let result = service_client
+        .scale_checkers(3)
+        .send_recv(program_id)
+        .await
+        .unwrap();
+    assert_eq!(result, Err(ManagerError::CheckerCodeNotSet));
+
+    let result = user_client
+        .set_checker_code(checker_code_id)
+        .send_recv(program_id)
+        .await
+        .unwrap();
+    assert_eq!(result, Err(ManagerError::NotAdmin));
+
+    service_client
+        .set_checker_code(checker_code_id)
+        .send_recv(program_id)
+        .await
+        .unwrap()
+        .unwrap();
+    let code_id = service_client
+        .get_checker_code()
+        .recv(program_id)
+        .await
+        .unwrap();
+    assert_eq!(code_id, Some(checker_code_id));
+
+    service_client
+        .scale_checkers(3)
+        .send_recv(program_id)
+        .await
+        .unwrap()
+        .unwrap();
+    let checkers = service_client
+        .get_checkers()
+        .recv(program_id)
+        .await
+        .unwrap();
+    assert_eq!(checkers.len(), 3);
+
+    // The deployed checkers compute a job
+    let job_id = service_client
+        .create_job(job_params(4, 3, 100))
+        .send_recv(program_id)
+        .await
+        .unwrap()
+        .unwrap();
+    service_client
+        .generate_and_store_points(job_id, 12, false, true, 4)
+        .send_recv(program_id)
+        .await
+        .unwrap()
+        .unwrap();
+    for _i in 0..5 {
+        remoting.system().run_next_block();
+    }
+    let info = service_client
+        .get_job(job_id)
+        .recv(program_id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(info.status, JobStatus::Completed);
+
+    // A checker registered by hand is not counted by scaling
+    let manual: ActorId = ACTOR_ID.into();
+    service_client
+        .add_checker_with_capabilities(manual, mandelbrot_capabilities(4))
+        .send_recv(program_id)
+        .await
+        .unwrap()
+        .unwrap();
+
+    // Scaling down keeps the first deployed checkers
+    service_client
+        .scale_checkers(1)
+        .send_recv(program_id)
+        .await
+        .unwrap()
+        .unwrap();
+    let remaining = service_client
+        .get_checkers()
+        .recv(program_id)
+        .await
+        .unwrap();
+    assert_eq!(remaining, [checkers[0], manual]);
+
+    let spawned = service_client
+        .spawn_checkers(2)
+        .send_recv(program_id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(spawned.len(), 2);
+    let checkers = service_client
+        .get_checkers()
+        .recv(program_id)
+        .await
+        .unwrap();
+    assert_eq!(checkers.len(), 4);
+    assert_eq!(checkers[2..], spawned);
+
+    // Scaling to 0 removes every deployed checker and keeps the manual one
+    service_client
+        .scale_checkers(0)
+        .send_recv(program_id)
+        .await
+        .unwrap()
+        .unwrap();
+    let remaining = service_client
+        .get_checkers()
+        .recv(program_id)
+        .await
+        .unwrap();
+    assert_eq!(remaining, [manual]);
+
+    // Programs that do not answer the capabilities query are reported and
+    // left out. The manager code has the same constructor as the checker.
+    let manager_code_id = remoting.system().submit_code(manager::WASM_BINARY);
+    service_client
+        .set_checker_code(manager_code_id)
+        .send_recv(program_id)
+        .await
+        .unwrap()
+        .unwrap();
+    let mut listener = manager_client::manager::events::listener(remoting.clone());
+    let mut events = listener.listen().await.unwrap();
+    let spawned = service_client
+        .spawn_checkers(1)
+        .send_recv(program_id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(spawned.is_empty());
+    let (source, event) = events.next().await.unwrap();
+    assert_eq!(source, program_id);
+    let ManagerEvents::CheckersSpawned {
+        checkers,
+        incompatible,
+    } = event
+    else {
+        panic!("Unexpected event");
+    };
+    assert!(checkers.is_empty());
+    assert_eq!(incompatible.len(), 1);
+    let remaining = service_client
+        .get_checkers()
+        .recv(program_id)
+        .await
+        .unwrap();
+    assert_eq!(remaining, [manual]);
+}
+
+#[tokio::test]
+async fn capability_negotiation() {
+    let Setup {
+        remoting,
+        program_id,
+        checkers,
+        ..
+    } = setup(2).await;
+
+    let mut service_client = manager_client::Manager::new(remoting.clone());
+
+    let result = service_client
+        .add_checker_with_capabilities(
+            checkers[0],
+            Capabilities {
+                version: 3,
+                ..mandelbrot_capabilities(3)
+            },
+        )
+        .send_recv(program_id)
+        .await
+        .unwrap();
+    assert_eq!(result, Err(ManagerError::IncompatibleChecker));
+
+    // The first checker takes 3 Mandelbrot points at a time, the second one
+    // reports its own capabilities
+    service_client
+        .add_checker_with_capabilities(checkers[0], mandelbrot_capabilities(3))
+        .send_recv(program_id)
+        .await
+        .unwrap()
+        .unwrap();
+    service_client
+        .add_checkers(vec![checkers[1]])
+        .send_recv(program_id)
+        .await
+        .unwrap()
+        .unwrap();
+    let capabilities = service_client
+        .get_checker_capabilities(checkers[1])
+        .recv(program_id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(capabilities.version, 2);
+    assert_eq!(capabilities.max_batch_size, 500);
+    assert_eq!(capabilities.fractals.len(), 4);
+
+    let julia_job = service_client
+        .create_job(JobParams {
+            fractal: Fractal::Julia {
+                c_re: FixedPoint { num: 0, scale: 0 },
+                c_im: FixedPoint { num: 0, scale: 0 },
+            },
+            ..job_params(4, 2, 100)
+        })
+        .send_recv(program_id)
+        .await
+        .unwrap()
+        .unwrap();
+    let mandelbrot_job = service_client
+        .create_job(job_params(4, 2, 100))
+        .send_recv(program_id)
+        .await
+        .unwrap()
+        .unwrap();
+    for job_id in [julia_job, mandelbrot_job] {
+        service_client
+            .generate_and_store_points(job_id, 8, false, false, 0)
+            .send_recv(program_id)
+            .await
+            .unwrap()
+            .unwrap();
+    }
+
+    // The Julia job only goes to the second checker, in one batch of the
+    // checker's own size, and the first one takes 3 Mandelbrot points
+    let mut processed = Vec::new();
+    for _round in 0..2 {
+        service_client
+            .check_points_set(0, false)
+            .send_recv(program_id)
+            .await
+            .unwrap()
+            .unwrap();
+        for _i in 0..3 {
+            remoting.system().run_next_block();
+        }
+        for checker in checkers.iter().copied() {
+            let stats = service_client
+                .get_checker_stats(checker)
+                .recv(program_id)
+                .await
+                .unwrap()
+                .unwrap();
+            processed.push(stats.points_processed);
+        }
+    }
+    assert_eq!(processed, [3, 8, 6, 10]);
+
+    for job_id in [julia_job, mandelbrot_job] {
+        let info = service_client
+            .get_job(job_id)
+            .recv(program_id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(info.status, JobStatus::Completed);
+    }
+}
+
+#[tokio::test]
+async fn unanswered_batches() {
+    let Setup {
+        remoting,
+        program_id,
+        checkers,
+        ..
+    } = setup(1).await;
+
+    let mut service_client = manager_client::Manager::new(remoting.clone());
+
+    // The test account acts as a checker whose first batch runs out of gas
+    let silent: ActorId = ACTOR_ID.into();
+    service_client
+        .add_checkers(checkers.clone())
+        .send_recv(program_id)
+        .await
+        .unwrap()
+        .unwrap();
+    service_client
+        .add_checker_with_capabilities(silent, mandelbrot_capabilities(4))
+        .send_recv(program_id)
+        .await
+        .unwrap()
+        .unwrap();
+
+    let job_id = service_client
+        .create_job(job_params(4, 2, 100))
+        .send_recv(program_id)
+        .await
+        .unwrap()
+        .unwrap();
+    service_client
+        .generate_and_store_points(job_id, 8, false, false, 0)
+        .send_recv(program_id)
+        .await
+        .unwrap()
+        .unwrap();
+    for _ in 0..2 {
+        service_client
+            .send_next_batch(silent, 4)
+            .send_recv(program_id)
+            .await
+            .unwrap()
+            .unwrap();
+    }
+
+    // The reply to the second batch is matched by its id, not taken for the first one
+    service_client
+        .result_calculated(1, (4..8).collect(), vec![0; 4], vec![])
+        .send_recv(program_id)
+        .await
+        .unwrap()
+        .unwrap();
+    let result = service_client
+        .result_calculated(1, (4..8).collect(), vec![0; 4], vec![])
+        .send_recv(program_id)
+        .await
+        .unwrap();
+    assert!(matches!(result, Err(ManagerError::InvalidResults)));
+    let stats = service_client
+        .get_checker_stats(silent)
+        .recv(program_id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(stats.batches_completed, 1);
+    assert_eq!(stats.pending_batches, 1);
+
+    // Once it times out, the first batch goes to the other checker
+    for _ in 0..BATCH_TIMEOUT_BLOCKS {
+        remoting.system().run_next_block();
+    }
+    service_client
+        .check_points_set(4, false)
+        .send_recv(program_id)
+        .await
+        .unwrap()
+        .unwrap();
+    for _ in 0..3 {
+        remoting.system().run_next_block();
+    }
+
+    let stats = service_client
+        .get_checker_stats(silent)
+        .recv(program_id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(stats.failures, 2);
+    assert_eq!(stats.pending_batches, 0);
+    let info = service_client
+        .get_job(job_id)
+        .recv(program_id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(info.status, JobStatus::Completed);
+}
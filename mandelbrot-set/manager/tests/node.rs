@@ -1,10 +1,30 @@
-use gclient::{EventProcessor, GearApi, Result};
-use manager_client::{FixedPoint, PointResult};
-use sails_rs::{ActorId, Decode, Encode};
+use gclient::{Event, EventProcessor, GearApi, GearEvent, Result};
+use gear_core::ids::ProgramId;
+use manager_client::{
+    manager::events::ManagerEvents, FixedPoint, Fractal, JobParams, ManagerError, ResultsPage,
+};
+use sails_rs::{events::EventIo, ActorId, Decode, Encode};
 mod utils;
-use tokio::time::{sleep, Duration};
+use tokio::time::{timeout, Duration};
 use utils::*;
 
+/// How long the checkers get to reply to the batches of a `CheckPointsSet`.
+const CHECK_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// The number of points of a `BatchCompleted` event of the manager.
+fn completed_points(manager_id: ProgramId, event: Event) -> Option<u32> {
+    let Event::Gear(GearEvent::UserMessageSent { message, .. }) = event else {
+        return None;
+    };
+    if message.source.0.as_slice() != manager_id.as_ref() {
+        return None;
+    }
+    match ManagerEvents::decode_event(message.payload.0).ok()? {
+        ManagerEvents::BatchCompleted { count, .. } => Some(count),
+        _ => None,
+    }
+}
+
 #[tokio::test]
 async fn test_check_point_set() -> Result<()> {
     let api = GearApi::dev().await?;
@@ -13,61 +33,90 @@ async fn test_check_point_set() -> Result<()> {
     assert!(listener.blocks_running().await?);
 
     // Init
-    let (message_id, manager_id, checker_ids) = init(&api).await;
+    let (message_id, manager_id, checker_code_id) = init(&api).await;
     assert!(listener.message_processed(message_id).await?.succeed());
     println!("Manager is uploaded");
 
-    // Add checkers
-    let message_id = send_request!(api: &api, program_id: manager_id, service_name: "Manager", action: "AddCheckers", payload: (checker_ids));
+    // Deploy 100 checkers from the manager
+    let message_id = send_request!(api: &api, program_id: manager_id, service_name: "Manager", action: "SetCheckerCode", payload: (checker_code_id));
     assert!(listener.message_processed(message_id).await?.succeed());
+    for i in 0..10 {
+        let count: u32 = 10;
+        let message_id = send_request!(api: &api, program_id: manager_id, service_name: "Manager", action: "SpawnCheckers", payload: (count));
+        assert!(listener.message_processed(message_id).await?.succeed());
+        println!(
+            "Batch {} deployed, amount of checkers: {}",
+            i + 1,
+            (i + 1) * count
+        );
+    }
     println!("Checkers are added");
 
+    // Create a render job
+    let params = JobParams {
+        fractal: Fractal::Mandelbrot,
+        width: 600,
+        height: 600,
+        x_min: FixedPoint { num: -2, scale: 0 },
+        x_max: FixedPoint { num: 1, scale: 0 },
+        y_min: FixedPoint { num: -15, scale: 1 },
+        y_max: FixedPoint { num: 15, scale: 1 },
+        max_iter: 1000,
+        smooth: false,
+        deep_zoom: None,
+        adaptive: false,
+    };
+    let message_id = send_request!(api: &api, program_id: manager_id, service_name: "Manager", action: "CreateJob", payload: (params));
+    assert!(listener.message_processed(message_id).await?.succeed());
+    let job_id: u32 = 0;
+    println!("Job is created");
+
     // Generate points
     for _i in 0..12 {
-        let width: u32 = 600;
-        let height = 600;
-        let x_min = FixedPoint { num: -2, scale: 0 };
-        let x_max = FixedPoint { num: 1, scale: 0 };
-        let y_min = FixedPoint { num: -15, scale: 2 };
-        let y_max = FixedPoint { num: 15, scale: 1 };
         let point_per_call: u32 = 30_000;
-        let max_iter: u32 = 0;
         let batch_size: u32 = 0;
         let continue_generation = false;
         let check_points_after_generation = false;
-        let message_id = send_request!(api: &api, program_id: manager_id, service_name: "Manager", action: "GenerateAndStorePoints", payload: (width, height, x_min, x_max, y_min, y_max, point_per_call, continue_generation, check_points_after_generation, max_iter, batch_size));
+        let message_id = send_request!(api: &api, program_id: manager_id, service_name: "Manager", action: "GenerateAndStorePoints", payload: (job_id, point_per_call, continue_generation, check_points_after_generation, batch_size));
         assert!(listener.message_processed(message_id).await?.succeed());
         println!("{} are generated", (_i + 1) * point_per_call);
     }
 
     println!("Points are generated");
 
-    // Check point set
-    let max_iter: u32 = 1000;
+    // Check point set. Every checker gets one batch, and the events of their
+    // replies are counted from a listener subscribed before the request.
     let batch_size: u32 = 20;
     let continue_checking = false;
-    for _i in 0..1 {
-        let message_id = send_request!(api: &api, program_id: manager_id, service_name: "Manager", action: "CheckPointsSet", payload: (max_iter, batch_size, continue_checking));
-        assert!(listener.message_processed(message_id).await?.succeed());
-        println!("Sent {} message to check points", _i + 1);
-    }
+    let expected = 100 * batch_size;
+    let mut events = api.subscribe().await?;
+    let message_id = send_request!(api: &api, program_id: manager_id, service_name: "Manager", action: "CheckPointsSet", payload: (batch_size, continue_checking));
+    assert!(listener.message_processed(message_id).await?.succeed());
+    println!("Sent the message to check points");
 
-    sleep(Duration::from_secs(5)).await;
+    let completed = timeout(
+        CHECK_TIMEOUT,
+        events.proc_many(
+            |event| completed_points(manager_id, event),
+            |counts| {
+                let done = counts.iter().sum::<u32>() >= expected;
+                (counts, done)
+            },
+        ),
+    )
+    .await
+    .unwrap_or_else(|_| panic!("The checkers did not reply within {CHECK_TIMEOUT:?}"))?;
+    assert_eq!(completed.iter().sum::<u32>(), expected);
 
     // Get checked points
-    let start_index: u32 = 0;
-    let end_index: u32 = 2_000;
-    let point_results = get_state!(api: &api, listener: listener, program_id: manager_id, service_name: "Manager", action: "GetResults", return_type: Vec<PointResult>, payload: (start_index, end_index));
+    let cursor: u32 = 0;
+    let page = get_state!(api: &api, listener: listener, program_id: manager_id, service_name: "Manager", action: "GetResults", return_type: Result<ResultsPage, ManagerError>, payload: (job_id, cursor, expected)).expect("Job exists");
+    let point_results = page.results;
+    assert_eq!(point_results.len(), expected as usize);
 
-    if point_results.iter().all(|point| point.checked) {
-        println!("All points are checked!");
-    } else {
-        let unchecked_count = point_results.iter().filter(|point| !point.checked).count();
-        println!(
-            "Some points are not checked. Unchecked points: {}",
-            unchecked_count
-        );
-    }
+    let unchecked_count = point_results.iter().filter(|point| !point.checked).count();
+    assert_eq!(unchecked_count, 0, "Some points are not checked");
+    println!("All points are checked!");
 
     Ok(())
 }
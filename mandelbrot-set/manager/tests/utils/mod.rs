@@ -1,5 +1,5 @@
 use gclient::GearApi;
-use gear_core::ids::{MessageId, ProgramId};
+use gear_core::ids::{CodeId, MessageId, ProgramId};
 use sails_rs::{ActorId, Encode};
 
 pub const USERS_STR: &[&str] = &["//John", "//Mike", "//Dan"];
@@ -47,45 +47,18 @@ pub async fn get_new_client(api: &GearApi, name: &str) -> GearApi {
     api.clone().with(name).expect("Unable to change signer.")
 }
 
-pub async fn init(api: &GearApi) -> (MessageId, ProgramId, Vec<ActorId>) {
+pub async fn init(api: &GearApi) -> (MessageId, ProgramId, CodeId) {
     let request = ["New".encode()].concat();
 
     let path_to_checker = "../target/wasm32-unknown-unknown/release/mandelbrot_checker.opt.wasm";
     let path_to_manager = "../target/wasm32-unknown-unknown/release/manager.opt.wasm";
 
-    // init 100 checkers
-    let mut checkers: Vec<ActorId> = Vec::new();
-    for i in 0..10 {
-        let args: Vec<_> = (0..10)
-            .map(|j| {
-                let mut salt = gclient::now_micros().to_le_bytes();
-                salt[0] ^= i as u8;
-                salt[1] ^= j as u8;
-
-                (
-                    gclient::code_from_os(path_to_checker).expect("Unable to read code from OS"),
-                    salt,
-                    request.clone(),
-                    5_000_000_000,
-                    0,
-                )
-            })
-            .collect();
-        let (messages, _hash) = api
-            .upload_program_bytes_batch(args)
-            .await
-            .expect("Unable to upload programs batch");
-
-        let uploaded_checkers: Vec<ActorId> = messages
-            .into_iter()
-            .filter_map(|res| res.ok().map(|(_, program_id)| program_id))
-            .collect();
-        checkers.extend(uploaded_checkers);
-        println!(
-            "Batch {} uploaded, amount of checkers: {}",
-            i + 1,
-            checkers.len()
-        );
+    // The manager deploys the checkers from this code
+    let checker_code = gclient::code_from_os(path_to_checker).expect("Unable to read code from OS");
+    let checker_code_id = CodeId::generate(&checker_code);
+    // The code is already stored if an earlier run uploaded it
+    if let Err(error) = api.upload_code(checker_code).await {
+        println!("Checker code is not uploaded: {error}");
     }
 
     let (message_id, program_id, _hash) = api
@@ -99,7 +72,7 @@ pub async fn init(api: &GearApi) -> (MessageId, ProgramId, Vec<ActorId>) {
         .await
         .expect("Error upload program bytes");
 
-    (message_id, program_id, checkers)
+    (message_id, program_id, checker_code_id)
 }
 
 #[macro_export]
@@ -1,9 +1,29 @@
 #![no_std]
 
-use rust_decimal::Decimal;
+use mandelbrot_common::{
+    capabilities::{Arithmetic, PROTOCOL_VERSION},
+    fractal::FractalKind,
+    kernel,
+    perturbation::Perturbation,
+};
+pub use mandelbrot_common::{perturbation::ReferenceOrbit, Capabilities, FixedPoint, Fractal};
 use sails_rs::{gstd::msg, prelude::*};
+
+/// Largest batch the checker takes: a batch of points at `MAX_ITER` has to fit
+/// in the gas of one message.
+pub const MAX_BATCH_SIZE: u32 = 500;
+pub const MAX_ITER: u32 = 100_000;
+
 struct MandelbrotCheckerService(());
 
+#[derive(Encode, Decode, TypeInfo, Clone, Debug, PartialEq, Eq)]
+pub enum CheckerError {
+    /// The batch has more than `MAX_BATCH_SIZE` points.
+    BatchTooLarge,
+    /// `max_iter` is above `MAX_ITER`.
+    MaxIterTooHigh,
+}
+
 #[derive(Encode, Decode, TypeInfo, Clone)]
 pub struct Point {
     pub index: u32,
@@ -11,56 +31,82 @@ pub struct Point {
     pub c_im: FixedPoint,
 }
 
-#[derive(Encode, Decode, TypeInfo, Clone)]
-pub struct FixedPoint {
-    pub num: i64,
-    pub scale: u32,
-}
-
 #[sails_rs::service]
 impl MandelbrotCheckerService {
     pub fn new() -> Self {
         Self(())
     }
 
-    pub fn check_mandelbrot_points(&mut self, points: Vec<Point>, max_iter: u32) {
-        let (indexes, results): (Vec<u32>, Vec<u32>) = points
-            .into_iter()
-            .map(|point| {
-                let c_re = Decimal::new(point.c_re.num, point.c_re.scale);
-                let c_im = Decimal::new(point.c_im.num, point.c_im.scale);
-                (point.index, self.check_mandelbrot(c_re, c_im, max_iter))
-            })
-            .unzip();
+    /// Computes the escape iterations of the points for the given fractal and
+    /// sends them to the caller as `ResultCalculated`, with the `batch_id` the
+    /// manager gave the batch. With `smooth`, the smooth iteration counts follow
+    /// the integer ones, otherwise that list is empty.
+    ///
+    /// With a reference orbit, the points are offsets from its center in a
+    /// deep-zoom Mandelbrot job and are computed by perturbation.
+    ///
+    /// Batches beyond the advertised capabilities are rejected without a
+    /// `ResultCalculated`, as they may not fit in the gas of the message.
+    pub fn check_mandelbrot_points(
+        &mut self,
+        batch_id: u64,
+        points: Vec<Point>,
+        max_iter: u32,
+        smooth: bool,
+        fractal: Fractal,
+        reference: Option<ReferenceOrbit>,
+    ) -> Result<(), CheckerError> {
+        if points.len() > MAX_BATCH_SIZE as usize {
+            return Err(CheckerError::BatchTooLarge);
+        }
+        if max_iter > MAX_ITER {
+            return Err(CheckerError::MaxIterTooHigh);
+        }
+        let perturbation = reference.as_ref().map(Perturbation::new);
+        let mut indexes = Vec::with_capacity(points.len());
+        let mut results = Vec::with_capacity(points.len());
+        let mut smooth_results = Vec::new();
+        for point in points {
+            indexes.push(point.index);
+            let (c_re, c_im) = (&point.c_re, &point.c_im);
+            if smooth {
+                let (iter, smooth_iter) = match &perturbation {
+                    Some(perturbation) => perturbation.smooth_escape_time(c_re, c_im, max_iter),
+                    None => kernel::smooth_escape_time(&fractal, c_re, c_im, max_iter),
+                };
+                results.push(iter);
+                smooth_results.push(smooth_iter);
+            } else {
+                results.push(match &perturbation {
+                    Some(perturbation) => perturbation.escape_time(c_re, c_im, max_iter),
+                    None => kernel::escape_time(&fractal, c_re, c_im, max_iter),
+                });
+            }
+        }
         let payload = [
             "Manager".encode(),
             "ResultCalculated".encode(),
-            (indexes, results).encode(),
+            (batch_id, indexes, results, smooth_results).encode(),
         ]
         .concat();
         msg::send_bytes(msg::source(), payload, 0).expect("Error during msg sending");
+        Ok(())
     }
 
-    fn check_mandelbrot(&self, c_re: Decimal, c_im: Decimal, max_iter: u32) -> u32 {
-        let mut z_re = c_re;
-        let mut z_im = c_im;
-
-        // Threshold
-        let threshold = Decimal::from(4);
-
-        for _i in 0..max_iter {
-            let modulus_squared = z_re * z_re + z_im * z_im;
-            if modulus_squared > threshold {
-                return _i;
-            }
-
-            // z: z = z^2 + c
-            let new_re = z_re * z_re - z_im * z_im + c_re;
-            z_im = Decimal::from(2) * z_re * z_im + c_im;
-            z_re = new_re;
+    /// What the checker computes, queried by the manager before it registers the checker.
+    pub fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            version: PROTOCOL_VERSION,
+            fractals: vec![
+                FractalKind::Mandelbrot,
+                FractalKind::Julia,
+                FractalKind::Multibrot,
+                FractalKind::BurningShip,
+            ],
+            arithmetic: vec![Arithmetic::FixedPoint, Arithmetic::Perturbation],
+            max_batch_size: MAX_BATCH_SIZE,
+            max_iter: MAX_ITER,
         }
-
-        max_iter
     }
 }
 
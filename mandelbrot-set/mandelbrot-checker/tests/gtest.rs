@@ -3,7 +3,9 @@ use sails_rs::{
     gtest::{calls::*, System},
 };
 
-use mandelbrot_checker_client::{traits::*, FixedPoint, Point};
+use mandelbrot_checker_client::{
+    traits::*, Arithmetic, CheckerError, FixedPoint, Fractal, FractalKind, Point,
+};
 
 const ACTOR_ID: u64 = 42;
 
@@ -200,8 +202,127 @@ async fn do_something_works() {
         },
     ];
     service_client
-        .check_mandelbrot_points(points, 1000)
+        .check_mandelbrot_points(0, points, 1000, false, Fractal::Mandelbrot, None)
+        .send_recv(program_id)
+        .await
+        .unwrap()
+        .unwrap();
+}
+
+#[tokio::test]
+async fn rejects_batches_beyond_capabilities() {
+    let system = System::new();
+    system.init_logger();
+    system.mint_to(ACTOR_ID, 100_000_000_000_000);
+
+    let remoting = GTestRemoting::new(system, ACTOR_ID.into());
+    remoting.system().init_logger();
+
+    let program_code_id = remoting
+        .system()
+        .submit_code(mandelbrot_checker::WASM_BINARY);
+
+    let program_factory =
+        mandelbrot_checker_client::MandelbrotCheckerFactory::new(remoting.clone());
+
+    let program_id = program_factory
+        .new()
+        .send_recv(program_code_id, b"salt")
+        .await
+        .unwrap();
+
+    let mut service_client = mandelbrot_checker_client::MandelbrotChecker::new(remoting.clone());
+
+    let point = |index| Point {
+        index,
+        c_re: FixedPoint { num: 0, scale: 0 },
+        c_im: FixedPoint { num: 0, scale: 0 },
+    };
+
+    // `MAX_BATCH_SIZE` is 500
+    let result = service_client
+        .check_mandelbrot_points(
+            0,
+            (0..501).map(point).collect(),
+            100,
+            false,
+            Fractal::Mandelbrot,
+            None,
+        )
+        .send_recv(program_id)
+        .await
+        .unwrap();
+    assert_eq!(result, Err(CheckerError::BatchTooLarge));
+
+    // `MAX_ITER` is 100,000
+    let result = service_client
+        .check_mandelbrot_points(1, vec![point(0)], 100_001, false, Fractal::Mandelbrot, None)
         .send_recv(program_id)
         .await
         .unwrap();
+    assert_eq!(result, Err(CheckerError::MaxIterTooHigh));
+
+    // The limits themselves are accepted
+    service_client
+        .check_mandelbrot_points(
+            2,
+            (0..500).map(point).collect(),
+            100,
+            false,
+            Fractal::Mandelbrot,
+            None,
+        )
+        .send_recv(program_id)
+        .await
+        .unwrap()
+        .unwrap();
+    service_client
+        .check_mandelbrot_points(3, vec![point(0)], 100_000, false, Fractal::Mandelbrot, None)
+        .send_recv(program_id)
+        .await
+        .unwrap()
+        .unwrap();
+}
+
+#[tokio::test]
+async fn capabilities() {
+    let system = System::new();
+    system.init_logger();
+    system.mint_to(ACTOR_ID, 100_000_000_000_000);
+
+    let remoting = GTestRemoting::new(system, ACTOR_ID.into());
+    remoting.system().init_logger();
+
+    let program_code_id = remoting
+        .system()
+        .submit_code(mandelbrot_checker::WASM_BINARY);
+
+    let program_factory =
+        mandelbrot_checker_client::MandelbrotCheckerFactory::new(remoting.clone());
+
+    let program_id = program_factory
+        .new()
+        .send_recv(program_code_id, b"salt")
+        .await
+        .unwrap();
+
+    let service_client = mandelbrot_checker_client::MandelbrotChecker::new(remoting.clone());
+
+    let capabilities = service_client
+        .capabilities()
+        .recv(program_id)
+        .await
+        .unwrap();
+    assert_eq!(capabilities.version, 2);
+    assert_eq!(capabilities.fractals.len(), 4);
+    assert!(capabilities
+        .fractals
+        .iter()
+        .any(|kind| matches!(kind, FractalKind::Mandelbrot)));
+    assert!(capabilities
+        .arithmetic
+        .iter()
+        .any(|arithmetic| matches!(arithmetic, Arithmetic::Perturbation)));
+    assert_eq!(capabilities.max_batch_size, 500);
+    assert_eq!(capabilities.max_iter, 100_000);
 }
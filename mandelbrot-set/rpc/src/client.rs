@@ -0,0 +1,259 @@
+//! Typed client of a manager deployed on gear.exe.
+//!
+//! Queries are answered by the gear.exe node with
+//! `program_calculateReplyForHandle`, messages are sent to the mirror of the
+//! program through the Ethereum node. Requests that fail in transport are
+//! retried with an exponential backoff; a message is only sent again when the
+//! node could not be reached, so it is never sent twice.
+
+use crate::config::Config;
+use reqwest::Client;
+use sails_rs::calls::ActionIo;
+use serde::{de::DeserializeOwned, Deserialize};
+use serde_json::{json, Value};
+use std::{fmt, time::Duration};
+
+/// Selector of `sendMessage(bytes,uint128)` on the program mirror.
+const SEND_MESSAGE_SELECTOR: [u8; 4] = [0xd5, 0x62, 0x42, 0x22];
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(60);
+
+#[derive(Debug)]
+pub enum ClientError {
+    /// The node could not be reached or answered with an HTTP error.
+    Transport(reqwest::Error),
+    /// The node answered with a JSON-RPC error object.
+    Rpc { code: i64, message: String },
+    /// The program replied with another code than `Success`.
+    Program { code: String, message: String },
+    /// The answer is not what the request expects.
+    Decode(String),
+}
+
+impl fmt::Display for ClientError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Transport(error) => write!(f, "Transport error: {error}"),
+            Self::Rpc { code, message } => write!(f, "RPC error {code}: {message}"),
+            Self::Program { code, message } if message.is_empty() => {
+                write!(f, "Program error {code}")
+            }
+            Self::Program { code, message } => write!(f, "Program error {code}: {message}"),
+            Self::Decode(message) => write!(f, "Decode error: {message}"),
+        }
+    }
+}
+
+impl std::error::Error for ClientError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Transport(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<reqwest::Error> for ClientError {
+    fn from(error: reqwest::Error) -> Self {
+        Self::Transport(error)
+    }
+}
+
+#[derive(Deserialize)]
+struct Response<T> {
+    result: Option<T>,
+    error: Option<RpcError>,
+}
+
+#[derive(Deserialize)]
+struct RpcError {
+    code: i64,
+    message: String,
+}
+
+/// Reply computed by `program_calculateReplyForHandle`.
+#[derive(Deserialize)]
+struct ReplyInfo {
+    payload: String,
+    /// `{ "Success": reason }` or `{ "Error": reason }`.
+    code: Value,
+}
+
+/// When a failed request may be sent again.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Retry {
+    /// After any transport error, for requests without side effects.
+    OnTransportError,
+    /// Only if the request did not reach the node.
+    OnConnectError,
+}
+
+pub struct GearExeClient {
+    http: Client,
+    config: Config,
+}
+
+impl GearExeClient {
+    pub fn new(config: Config) -> Result<Self, ClientError> {
+        let http = Client::builder().timeout(REQUEST_TIMEOUT).build()?;
+        Ok(Self { http, config })
+    }
+
+    /// Address of the manager mirror.
+    pub fn program_id(&self) -> &str {
+        &self.config.program_id
+    }
+
+    /// Computes the reply of the manager to the call `payload` of `A` without
+    /// sending a message.
+    pub async fn query<A: ActionIo>(&self, payload: Vec<u8>) -> Result<A::Reply, ClientError> {
+        let params = json!({
+            "source": self.config.source,
+            "program_id": self.config.program_id,
+            "payload": hex::encode(payload),
+            "value": 0
+        });
+        let reply: ReplyInfo = self
+            .call(
+                &self.config.node_url,
+                "program_calculateReplyForHandle",
+                params,
+                Retry::OnTransportError,
+            )
+            .await?;
+
+        let payload = hex::decode(reply.payload.trim_start_matches("0x"))
+            .map_err(|error| ClientError::Decode(format!("Reply payload: {error}")))?;
+        if reply.code.get("Success").is_none() {
+            // Error replies carry the panic message of the program, if any
+            return Err(ClientError::Program {
+                code: reply.code.to_string(),
+                message: String::from_utf8_lossy(&payload).into_owned(),
+            });
+        }
+        A::decode_reply(&payload).map_err(|error| ClientError::Decode(error.to_string()))
+    }
+
+    /// Sends the call `payload` to the manager through `sendMessage` of its
+    /// mirror and returns the hash of the transaction. The source account has
+    /// to be unlocked on the Ethereum node, which signs the transaction.
+    pub async fn send_message(&self, payload: Vec<u8>) -> Result<String, ClientError> {
+        let params = json!([{
+            "from": self.config.source,
+            "to": self.config.program_id,
+            "data": format!("0x{}", hex::encode(send_message_calldata(&payload, 0))),
+        }]);
+        self.call(
+            &self.config.eth_url,
+            "eth_sendTransaction",
+            params,
+            Retry::OnConnectError,
+        )
+        .await
+    }
+
+    async fn call<T: DeserializeOwned>(
+        &self,
+        url: &str,
+        method: &str,
+        params: Value,
+        retry: Retry,
+    ) -> Result<T, ClientError> {
+        let request = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": method,
+            "params": params,
+        });
+
+        let mut delay = self.config.retry_delay;
+        let mut attempt = 0;
+        loop {
+            match self.try_call(url, &request).await {
+                Err(ClientError::Transport(error))
+                    if attempt < self.config.retries
+                        && (error.is_connect() || retry == Retry::OnTransportError) =>
+                {
+                    attempt += 1;
+                    eprintln!(
+                        "{method} failed ({error}), retrying in {}ms ({attempt}/{})",
+                        delay.as_millis(),
+                        self.config.retries
+                    );
+                    tokio::time::sleep(delay).await;
+                    delay *= 2;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    async fn try_call<T: DeserializeOwned>(
+        &self,
+        url: &str,
+        request: &Value,
+    ) -> Result<T, ClientError> {
+        let response = self
+            .http
+            .post(url)
+            .header("Content-Type", "application/json;charset=utf-8")
+            .json(request)
+            .send()
+            .await?
+            .error_for_status()?;
+        let text = response.text().await?;
+        let response: Response<T> = serde_json::from_str(&text)
+            .map_err(|error| ClientError::Decode(format!("{error} in {text}")))?;
+        match (response.result, response.error) {
+            (_, Some(RpcError { code, message })) => Err(ClientError::Rpc { code, message }),
+            (Some(result), None) => Ok(result),
+            (None, None) => Err(ClientError::Decode(format!("No result in {text}"))),
+        }
+    }
+}
+
+/// ABI-encodes `sendMessage(bytes _payload, uint128 _value)`.
+fn send_message_calldata(payload: &[u8], value: u128) -> Vec<u8> {
+    let word = |value: u128| {
+        let mut word = [0u8; 32];
+        word[16..].copy_from_slice(&value.to_be_bytes());
+        word
+    };
+    let mut data = SEND_MESSAGE_SELECTOR.to_vec();
+    // Offset of the bytes after the two head words
+    data.extend(word(64));
+    data.extend(word(value));
+    data.extend(word(payload.len() as u128));
+    data.extend(payload);
+    data.resize(data.len() + (32 - payload.len() % 32) % 32, 0);
+    data
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hex_literal::hex;
+
+    #[test]
+    fn send_message_calldata_is_abi_encoded() {
+        let data = send_message_calldata(&[1, 2, 3], 5);
+        let expected = [
+            // keccak256("sendMessage(bytes,uint128)")[..4]
+            hex!("d5624222").as_slice(),
+            &hex!("0000000000000000000000000000000000000000000000000000000000000040"),
+            &hex!("0000000000000000000000000000000000000000000000000000000000000005"),
+            &hex!("0000000000000000000000000000000000000000000000000000000000000003"),
+            &hex!("0102030000000000000000000000000000000000000000000000000000000000"),
+        ]
+        .concat();
+        assert_eq!(data, expected);
+    }
+
+    #[test]
+    fn send_message_calldata_pads_payload_to_words() {
+        for (len, padded) in [(0, 0), (1, 32), (32, 32), (33, 64)] {
+            let data = send_message_calldata(&vec![0xff; len], 0);
+            assert_eq!(data.len(), 4 + 3 * 32 + padded, "payload of {len} bytes");
+            assert_eq!(data[4 + 3 * 32..][..len], vec![0xff; len]);
+        }
+    }
+}
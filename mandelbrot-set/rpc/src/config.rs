@@ -0,0 +1,141 @@
+//! Deployment settings of the renderer.
+//!
+//! Settings are read from a TOML file, `rpc.toml` in the working directory
+//! unless `--config` names another one. Flags given on the command line take
+//! precedence over the file, and the file over the defaults of a local dev node.
+
+use serde::Deserialize;
+use std::{error::Error, fs, path::Path, time::Duration};
+
+pub const DEFAULT_CONFIG_PATH: &str = "rpc.toml";
+pub const DEFAULT_NODE_URL: &str = "http://localhost:9944";
+pub const DEFAULT_ETH_URL: &str = "http://localhost:8545";
+pub const DEFAULT_SOURCE: &str = "0xf823ba3F10922DCca6970D1e012D8701f462Aa33";
+pub const DEFAULT_RETRIES: u32 = 3;
+pub const DEFAULT_RETRY_DELAY_MS: u64 = 500;
+
+/// Settings as written in the config file, every one of them optional.
+#[derive(Deserialize, Default, Debug)]
+#[serde(default, deny_unknown_fields)]
+pub struct ConfigFile {
+    /// gear.exe node answering `program_calculateReplyForHandle`.
+    pub node_url: Option<String>,
+    /// Ethereum node that signs and sends the messages to the program mirror.
+    pub eth_url: Option<String>,
+    /// Address of the manager mirror.
+    pub program_id: Option<String>,
+    /// Account the queries are made from and the messages are sent from.
+    pub source: Option<String>,
+    /// Attempts after the first one when a request fails in transport.
+    pub retries: Option<u32>,
+    /// Delay before the first retry, doubled after every attempt.
+    pub retry_delay_ms: Option<u64>,
+}
+
+impl ConfigFile {
+    /// Reads the file at `path`. Without an explicit path a missing
+    /// `rpc.toml` is the same as an empty one.
+    pub fn load(path: Option<&Path>) -> Result<Self, Box<dyn Error>> {
+        let (path, required) = match path {
+            Some(path) => (path, true),
+            None => (Path::new(DEFAULT_CONFIG_PATH), false),
+        };
+        match fs::read_to_string(path) {
+            Ok(text) => toml::from_str(&text)
+                .map_err(|error| format!("Invalid config {}: {error}", path.display()).into()),
+            Err(error) if !required && error.kind() == std::io::ErrorKind::NotFound => {
+                Ok(Self::default())
+            }
+            Err(error) => Err(format!("Cannot read config {}: {error}", path.display()).into()),
+        }
+    }
+
+    /// Overrides the file with the settings given on the command line.
+    pub fn merge(self, overrides: ConfigFile) -> Result<Config, Box<dyn Error>> {
+        let program_id = overrides.program_id.or(self.program_id).ok_or(
+            "The program id is not set: pass --program-id or set program_id in the config",
+        )?;
+        Ok(Config {
+            node_url: overrides
+                .node_url
+                .or(self.node_url)
+                .unwrap_or_else(|| DEFAULT_NODE_URL.to_string()),
+            eth_url: overrides
+                .eth_url
+                .or(self.eth_url)
+                .unwrap_or_else(|| DEFAULT_ETH_URL.to_string()),
+            program_id,
+            source: overrides
+                .source
+                .or(self.source)
+                .unwrap_or_else(|| DEFAULT_SOURCE.to_string()),
+            retries: overrides
+                .retries
+                .or(self.retries)
+                .unwrap_or(DEFAULT_RETRIES),
+            retry_delay: Duration::from_millis(
+                overrides
+                    .retry_delay_ms
+                    .or(self.retry_delay_ms)
+                    .unwrap_or(DEFAULT_RETRY_DELAY_MS),
+            ),
+        })
+    }
+}
+
+/// Settings the commands run with.
+#[derive(Debug)]
+pub struct Config {
+    pub node_url: String,
+    pub eth_url: String,
+    pub program_id: String,
+    pub source: String,
+    pub retries: u32,
+    pub retry_delay: Duration,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn file(text: &str) -> ConfigFile {
+        toml::from_str(text).unwrap()
+    }
+
+    #[test]
+    fn command_line_overrides_file_and_file_overrides_defaults() {
+        let config = file(
+            r#"
+            node_url = "http://node:9944"
+            program_id = "0x01"
+            retries = 5
+            "#,
+        )
+        .merge(ConfigFile {
+            program_id: Some("0x02".to_string()),
+            retry_delay_ms: Some(100),
+            ..ConfigFile::default()
+        })
+        .unwrap();
+
+        assert_eq!(config.program_id, "0x02");
+        assert_eq!(config.node_url, "http://node:9944");
+        assert_eq!(config.retries, 5);
+        assert_eq!(config.retry_delay, Duration::from_millis(100));
+        assert_eq!(config.eth_url, DEFAULT_ETH_URL);
+        assert_eq!(config.source, DEFAULT_SOURCE);
+    }
+
+    #[test]
+    fn program_id_is_required() {
+        let error = ConfigFile::default()
+            .merge(ConfigFile::default())
+            .unwrap_err();
+        assert!(error.to_string().contains("--program-id"));
+    }
+
+    #[test]
+    fn unknown_settings_are_rejected() {
+        assert!(toml::from_str::<ConfigFile>("node = \"http://node:9944\"").is_err());
+    }
+}
@@ -0,0 +1,262 @@
+//! Export of the raw results of a job, to analyze them or compare runs exactly.
+//!
+//! - `csv`: one line per downloaded point with its index, column, row,
+//!   coordinates, iteration count, smooth count and checked flag.
+//! - `npy`: the iteration counts as a `height x width` array of `<u4`, row 0 at
+//!   the top of the image. Pixels without a checked result hold `u32::MAX`.
+//! - `bin`: a 68-byte little-endian header followed by the grid:
+//!
+//!   | offset | size | field                                            |
+//!   |--------|------|--------------------------------------------------|
+//!   | 0      | 4    | magic `MBRG`                                     |
+//!   | 4      | 1    | format version, 1                                |
+//!   | 5      | 1    | bytes per iteration count, 2 or 4                |
+//!   | 6      | 1    | 1 if smooth counts follow the iteration counts   |
+//!   | 7      | 1    | reserved, 0                                      |
+//!   | 8      | 4    | width (`u32`)                                    |
+//!   | 12     | 4    | height (`u32`)                                   |
+//!   | 16     | 4    | `max_iter` (`u32`)                               |
+//!   | 20     | 48   | `x_min`, `x_max`, `y_min`, `y_max`, each as `num` (`i64`) and `scale` (`u32`) |
+//!
+//!   The iteration counts come next in pixel order, 2 bytes each when
+//!   `max_iter` fits below `u16::MAX`, with all bits set for pixels without a
+//!   checked result. Smooth jobs add one `f64` per pixel, NaN where unknown.
+
+use crate::{fixed_point, smooth};
+use clap::ValueEnum;
+use manager_client::{FixedPoint, JobParams, PointResult};
+use mandelbrot_common::grid::pixel_position;
+use std::{
+    fs::File,
+    io::{self, BufWriter, Write},
+    path::Path,
+};
+
+const BIN_MAGIC: &[u8; 4] = b"MBRG";
+const BIN_VERSION: u8 = 1;
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+pub enum Format {
+    Csv,
+    Npy,
+    Bin,
+}
+
+pub fn export(
+    format: Format,
+    path: &Path,
+    params: &JobParams,
+    points: &[PointResult],
+) -> io::Result<()> {
+    let mut out = BufWriter::new(File::create(path)?);
+    match format {
+        Format::Csv => write_csv(&mut out, params, points)?,
+        Format::Npy => write_npy(&mut out, params, points)?,
+        Format::Bin => write_bin(&mut out, params, points)?,
+    }
+    out.flush()
+}
+
+fn decimal(value: &FixedPoint) -> String {
+    fixed_point(value).to_decimal().to_string()
+}
+
+fn write_csv(out: &mut impl Write, params: &JobParams, points: &[PointResult]) -> io::Result<()> {
+    writeln!(out, "index,column,row,c_re,c_im,iter,smooth,checked")?;
+    for point in points {
+        let (column, row) = pixel_position(point.index, params.width);
+        writeln!(
+            out,
+            "{},{column},{row},{},{},{},{},{}",
+            point.index,
+            decimal(&point.c_re),
+            decimal(&point.c_im),
+            point.iter,
+            point.smooth.as_ref().map(decimal).unwrap_or_default(),
+            point.checked,
+        )?;
+    }
+    Ok(())
+}
+
+/// Iteration counts of every pixel, `None` where no checked result is known.
+fn grid(params: &JobParams, points: &[PointResult]) -> Vec<Option<u32>> {
+    let mut grid = vec![None; params.width as usize * params.height as usize];
+    for point in points.iter().filter(|point| point.checked) {
+        if let Some(cell) = grid.get_mut(point.index as usize) {
+            *cell = Some(point.iter);
+        }
+    }
+    grid
+}
+
+fn write_npy(out: &mut impl Write, params: &JobParams, points: &[PointResult]) -> io::Result<()> {
+    let mut header = format!(
+        "{{'descr': '<u4', 'fortran_order': False, 'shape': ({}, {}), }}",
+        params.height, params.width
+    );
+    // Version 1.0 pads the header with spaces so the data is 64-byte aligned
+    let unpadded = 10 + header.len() + 1;
+    header.push_str(&" ".repeat(unpadded.next_multiple_of(64) - unpadded));
+    header.push('\n');
+
+    out.write_all(b"\x93NUMPY\x01\x00")?;
+    out.write_all(&(header.len() as u16).to_le_bytes())?;
+    out.write_all(header.as_bytes())?;
+    for iter in grid(params, points) {
+        out.write_all(&iter.unwrap_or(u32::MAX).to_le_bytes())?;
+    }
+    Ok(())
+}
+
+fn write_bin(out: &mut impl Write, params: &JobParams, points: &[PointResult]) -> io::Result<()> {
+    let compact = params.max_iter < u16::MAX as u32;
+    out.write_all(BIN_MAGIC)?;
+    out.write_all(&[
+        BIN_VERSION,
+        if compact { 2 } else { 4 },
+        params.smooth as u8,
+        0,
+    ])?;
+    for value in [params.width, params.height, params.max_iter] {
+        out.write_all(&value.to_le_bytes())?;
+    }
+    for bound in [&params.x_min, &params.x_max, &params.y_min, &params.y_max] {
+        out.write_all(&bound.num.to_le_bytes())?;
+        out.write_all(&bound.scale.to_le_bytes())?;
+    }
+
+    for iter in grid(params, points) {
+        if compact {
+            out.write_all(&iter.map_or(u16::MAX, |iter| iter as u16).to_le_bytes())?;
+        } else {
+            out.write_all(&iter.unwrap_or(u32::MAX).to_le_bytes())?;
+        }
+    }
+
+    if params.smooth {
+        let mut values = vec![f64::NAN; params.width as usize * params.height as usize];
+        for point in points.iter().filter(|point| point.checked) {
+            if let (Some(cell), Some(value)) = (values.get_mut(point.index as usize), smooth(point))
+            {
+                *cell = value;
+            }
+        }
+        for value in values {
+            out.write_all(&value.to_le_bytes())?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use manager_client::Fractal;
+
+    fn params(width: u32, height: u32, max_iter: u32, smooth: bool) -> JobParams {
+        JobParams {
+            fractal: Fractal::Mandelbrot,
+            width,
+            height,
+            x_min: FixedPoint { num: -2, scale: 0 },
+            x_max: FixedPoint { num: 1, scale: 0 },
+            y_min: FixedPoint { num: -15, scale: 1 },
+            y_max: FixedPoint { num: 15, scale: 1 },
+            max_iter,
+            smooth,
+            deep_zoom: None,
+            adaptive: false,
+        }
+    }
+
+    fn point(index: u32, iter: u32, checked: bool, smooth: Option<FixedPoint>) -> PointResult {
+        PointResult {
+            index,
+            c_re: FixedPoint { num: 0, scale: 0 },
+            c_im: FixedPoint { num: 0, scale: 0 },
+            iter,
+            smooth,
+            checked,
+        }
+    }
+
+    #[test]
+    fn npy_header_round_trip() {
+        let params = params(3, 2, 100, false);
+        // Index 2 is missing and index 4 is not checked
+        let points = [0, 1, 3, 4, 5].map(|index| point(index, index * 10, index != 4, None));
+        let mut out = Vec::new();
+        write_npy(&mut out, &params, &points).unwrap();
+
+        assert_eq!(&out[..8], b"\x93NUMPY\x01\x00");
+        let header_len = u16::from_le_bytes([out[8], out[9]]) as usize;
+        let data = 10 + header_len;
+        assert_eq!(data % 64, 0);
+        let header = std::str::from_utf8(&out[10..data]).unwrap();
+        assert!(header.ends_with('\n'));
+        assert_eq!(
+            header.trim_end(),
+            "{'descr': '<u4', 'fortran_order': False, 'shape': (2, 3), }"
+        );
+
+        let iters: Vec<u32> = out[data..]
+            .chunks(4)
+            .map(|bytes| u32::from_le_bytes(bytes.try_into().unwrap()))
+            .collect();
+        assert_eq!(iters, [0, 10, u32::MAX, 30, u32::MAX, 50]);
+    }
+
+    #[test]
+    fn npy_header_is_padded_for_any_shape() {
+        for (width, height) in [(1, 1), (600, 600), (100_000, 10)] {
+            let mut out = Vec::new();
+            write_npy(&mut out, &params(width, height, 100, false), &[]).unwrap();
+            let header_len = u16::from_le_bytes([out[8], out[9]]) as usize;
+            assert_eq!((10 + header_len) % 64, 0);
+            assert_eq!(out.len(), 10 + header_len + 4 * (width * height) as usize);
+        }
+    }
+
+    #[test]
+    fn bin_layout() {
+        let params = params(2, 1, 100, false);
+        let mut out = Vec::new();
+        write_bin(&mut out, &params, &[point(1, 7, true, None)]).unwrap();
+
+        assert_eq!(out.len(), 68 + 2 * 2);
+        assert_eq!(&out[..4], b"MBRG");
+        assert_eq!(out[4..8], [1, 2, 0, 0]);
+        let u32_at =
+            |offset: usize| u32::from_le_bytes(out[offset..offset + 4].try_into().unwrap());
+        assert_eq!([u32_at(8), u32_at(12), u32_at(16)], [2, 1, 100]);
+        let bounds: Vec<(i64, u32)> = out[20..68]
+            .chunks(12)
+            .map(|bound| {
+                (
+                    i64::from_le_bytes(bound[..8].try_into().unwrap()),
+                    u32::from_le_bytes(bound[8..].try_into().unwrap()),
+                )
+            })
+            .collect();
+        assert_eq!(bounds, [(-2, 0), (1, 0), (-15, 1), (15, 1)]);
+        assert_eq!(out[68..], [0xff, 0xff, 7, 0]);
+    }
+
+    #[test]
+    fn bin_wide_counts_and_smooth() {
+        let params = params(2, 1, 70_000, true);
+        let smooth = FixedPoint { num: 125, scale: 2 };
+        let mut out = Vec::new();
+        write_bin(&mut out, &params, &[point(0, 69_999, true, Some(smooth))]).unwrap();
+
+        assert_eq!(out.len(), 68 + 2 * 4 + 2 * 8);
+        assert_eq!(out[4..8], [1, 4, 1, 0]);
+        assert_eq!(out[68..72], 69_999u32.to_le_bytes());
+        assert_eq!(out[72..76], u32::MAX.to_le_bytes());
+        let smooth_at =
+            |offset: usize| f64::from_le_bytes(out[offset..offset + 8].try_into().unwrap());
+        assert_eq!(smooth_at(76), 1.25);
+        assert!(smooth_at(84).is_nan());
+    }
+}
@@ -0,0 +1,183 @@
+//! Concurrent download of the results of a job.
+//!
+//! The generated points are cut into pages of `page_size` results that are
+//! fetched with up to `concurrency` queries in flight. Checked results only
+//! change when the job is restarted or a lost dispute corrects them, and both
+//! bump `JobInfo::results_version`. So pages whose points are all checked are
+//! written to the cache as they arrive and read back from it by later runs
+//! while the version stays the same: an interrupted download resumes where it
+//! stopped. The cache of a job is dropped when the job turns out to have
+//! another results version or other parameters, e.g. after the manager was
+//! redeployed.
+
+use crate::client::GearExeClient;
+use futures::{stream, StreamExt, TryStreamExt};
+use indicatif::{ProgressBar, ProgressStyle};
+use manager_client::{manager::io, JobInfo, PointResult};
+use sails_rs::{Decode, Encode};
+use std::{
+    error::Error,
+    fs,
+    path::{Path, PathBuf},
+};
+
+pub struct FetchOptions {
+    /// Results per `GetResults` query, capped by the manager.
+    pub page_size: u32,
+    /// Queries in flight at the same time.
+    pub concurrency: usize,
+    /// Directory of the pages of this job, `None` to always download them.
+    pub cache: Option<PathBuf>,
+}
+
+/// Downloads the results of every generated point of the job, ordered by index.
+pub async fn fetch_results(
+    client: &GearExeClient,
+    job: &JobInfo,
+    options: &FetchOptions,
+) -> Result<Vec<PointResult>, Box<dyn Error>> {
+    let (job_id, params) = (job.id, &job.params);
+    let generated = client
+        .query::<io::GetPointsLen>(io::GetPointsLen::encode_call(job_id))
+        .await?;
+    if let Some(cache) = &options.cache {
+        prepare_cache(cache, job)?;
+    }
+
+    let progress = ProgressBar::new(generated as u64);
+    progress.set_style(ProgressStyle::with_template(
+        "{bar:40} {pos}/{len} points, {per_sec}, {eta} left",
+    )?);
+
+    let pages: Vec<(u32, u32)> = pages(params.width * params.height, options.page_size)
+        .into_iter()
+        .filter(|&(start, _)| start < generated)
+        .collect();
+    let mut pages = fetch_pages(client, job_id, &pages, options, &progress).await?;
+    progress.finish();
+
+    pages.sort_unstable_by_key(|(start, _)| *start);
+    Ok(pages.into_iter().flat_map(|(_, results)| results).collect())
+}
+
+/// Ranges of at most `page_size` indexes covering `0..total_points`.
+pub fn pages(total_points: u32, page_size: u32) -> Vec<(u32, u32)> {
+    let page_size = page_size.max(1);
+    (0..total_points)
+        .step_by(page_size as usize)
+        .map(|start| (start, start.saturating_add(page_size).min(total_points)))
+        .collect()
+}
+
+/// Downloads the given pages with up to `concurrency` queries in flight and
+/// returns them with their start, in the order they arrive.
+pub async fn fetch_pages(
+    client: &GearExeClient,
+    job_id: u32,
+    pages: &[(u32, u32)],
+    options: &FetchOptions,
+    progress: &ProgressBar,
+) -> Result<Vec<(u32, Vec<PointResult>)>, Box<dyn Error>> {
+    stream::iter(pages)
+        .map(|&(start, end)| async move {
+            let results = fetch_page(client, job_id, start, end, options).await?;
+            progress.inc(results.len() as u64);
+            Ok::<_, Box<dyn Error>>((start, results))
+        })
+        .buffer_unordered(options.concurrency.max(1))
+        .try_collect()
+        .await
+}
+
+/// Reads the results from `start` to `end` from the cache or downloads them,
+/// following `next_cursor` when the manager answers with fewer results than
+/// asked.
+async fn fetch_page(
+    client: &GearExeClient,
+    job_id: u32,
+    start: u32,
+    end: u32,
+    options: &FetchOptions,
+) -> Result<Vec<PointResult>, Box<dyn Error>> {
+    let path = options
+        .cache
+        .as_ref()
+        .map(|cache| cache.join(format!("page-{start}-{end}.scale")));
+    if let Some(results) = path.as_deref().and_then(read_cached_page) {
+        return Ok(results);
+    }
+
+    let mut results = Vec::with_capacity((end - start) as usize);
+    let mut cursor = Some(start);
+    while let Some(from) = cursor.filter(|&cursor| cursor < end) {
+        let page = client
+            .query::<io::GetResults>(io::GetResults::encode_call(job_id, from, end - from))
+            .await?
+            .map_err(|error| format!("GetResults failed: {error:?}"))?;
+        results.extend(page.results);
+        cursor = page.next_cursor;
+    }
+
+    if let Some(path) = path {
+        // Points still being generated or checked are fetched again next time
+        let complete = results.len() == (end - start) as usize;
+        if complete && results.iter().all(|point| point.checked) {
+            write_atomically(&path, &results.encode())?;
+        }
+    }
+    Ok(results)
+}
+
+fn read_cached_page(path: &Path) -> Option<Vec<PointResult>> {
+    let bytes = fs::read(path).ok()?;
+    Vec::<PointResult>::decode(&mut bytes.as_slice()).ok()
+}
+
+/// Creates the cache directory, emptied if it holds pages of other parameters
+/// or of another results version.
+pub fn prepare_cache(cache: &Path, job: &JobInfo) -> Result<(), Box<dyn Error>> {
+    let key_path = cache.join("job.scale");
+    let encoded = (&job.params, job.results_version).encode();
+    if fs::read(&key_path).ok().as_ref() == Some(&encoded) {
+        return Ok(());
+    }
+    if cache.exists() {
+        fs::remove_dir_all(cache)?;
+    }
+    fs::create_dir_all(cache)?;
+    write_atomically(&key_path, &encoded)
+}
+
+/// Writes through a temporary file, so a crash never leaves a truncated page.
+fn write_atomically(path: &Path, bytes: &[u8]) -> Result<(), Box<dyn Error>> {
+    let tmp = path.with_extension("tmp");
+    fs::write(&tmp, bytes)?;
+    fs::rename(tmp, path)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pages_cover_all_points() {
+        assert_eq!(pages(6, 2), [(0, 2), (2, 4), (4, 6)]);
+        assert_eq!(pages(7, 3), [(0, 3), (3, 6), (6, 7)]);
+        assert_eq!(pages(5, 10), [(0, 5)]);
+        assert!(pages(0, 10).is_empty());
+    }
+
+    #[test]
+    fn pages_have_at_least_one_point() {
+        assert_eq!(pages(2, 0), [(0, 1), (1, 2)]);
+    }
+
+    #[test]
+    fn last_page_ends_at_total_points() {
+        assert_eq!(
+            pages(u32::MAX, u32::MAX - 1),
+            [(0, u32::MAX - 1), (u32::MAX - 1, u32::MAX)]
+        );
+    }
+}
@@ -1,123 +1,446 @@
+mod client;
+mod config;
+mod export;
+mod fetch;
+mod palette;
+mod reference;
+mod watch;
+
+use clap::{Args, Parser, Subcommand};
+use client::GearExeClient;
+use config::ConfigFile;
+use fetch::FetchOptions;
 use image::{ImageBuffer, Rgb};
-use manager_client::{FixedPoint, PointResult};
-use parity_scale_codec::{Decode, Encode};
-use primitive_types::U256;
-use reqwest::Client;
-use sails_rs::calls::ActionIo;
+use manager_client::manager::io;
+use manager_client::{JobInfo, JobParams, JobStatus, PointResult};
+use mandelbrot_common::{
+    grid::{pixel_index, pixel_position},
+    FixedPoint, Viewport,
+};
+use palette::{Coloring, Colorizer, Interpolation, Palette};
+use reference::{Reference, Sample};
 use sails_rs::ActorId;
-use serde::Deserialize;
-use serde_json::json;
 use serde_json::to_string_pretty;
 use std::error::Error;
 use std::fs::File;
-use std::io::{self, Write};
+use std::io::Write;
+use std::path::PathBuf;
+use std::time::Duration;
+use watch::WatchOptions;
+
+/// Drives a Mandelbrot manager deployed on gear.exe and renders its results.
+#[derive(Parser)]
+struct Cli {
+    /// Config file, `rpc.toml` in the working directory by default.
+    #[arg(long, global = true)]
+    config: Option<PathBuf>,
+    #[command(flatten)]
+    overrides: Overrides,
+    #[command(subcommand)]
+    command: Command,
+}
+
+/// Settings that take precedence over the config file.
+#[derive(Args)]
+struct Overrides {
+    /// gear.exe node answering the queries.
+    #[arg(long, global = true)]
+    node_url: Option<String>,
+    /// Ethereum node the messages are sent through.
+    #[arg(long, global = true)]
+    eth_url: Option<String>,
+    /// Address of the manager mirror.
+    #[arg(long, global = true)]
+    program_id: Option<String>,
+    /// Account the queries and messages come from.
+    #[arg(long, global = true)]
+    source: Option<String>,
+    /// Attempts after the first one when a request fails in transport.
+    #[arg(long, global = true)]
+    retries: Option<u32>,
+    /// Delay before the first retry, doubled after every attempt.
+    #[arg(long, global = true)]
+    retry_delay_ms: Option<u64>,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Lists the registered checkers and writes them to a JSON file.
+    Checkers {
+        #[arg(long, default_value = "checkers.txt")]
+        output: PathBuf,
+    },
+    /// Shows the progress of a job, or of every job.
+    Status {
+        #[arg(long)]
+        job_id: Option<u32>,
+    },
+    /// Downloads the results of a job and draws them.
+    Render(RenderArgs),
+    /// Downloads the results of a job and writes them as raw data.
+    Export {
+        #[command(flatten)]
+        fetch: FetchArgs,
+        #[arg(long, value_enum, default_value_t = export::Format::Csv)]
+        format: export::Format,
+        #[arg(long)]
+        output: PathBuf,
+    },
+    /// Polls a job while it is computed and paints its pixels as they are checked.
+    Watch(WatchArgs),
+    /// Recomputes a job locally with the arithmetic of the checker and
+    /// reports the results that differ.
+    Diff(DiffArgs),
+    /// Sends `GenerateAndStorePoints` for a job.
+    Generate {
+        #[arg(long, default_value_t = 0)]
+        job_id: u32,
+        #[arg(long, default_value_t = 50_000)]
+        points_per_call: u32,
+        /// Generate a single step instead of continuing until the grid is complete.
+        #[arg(long)]
+        once: bool,
+        /// Start checking the points once they are generated.
+        #[arg(long)]
+        check: bool,
+        /// Points per batch, 0 for as many as each checker takes.
+        #[arg(long, default_value_t = 0)]
+        batch_size: u32,
+    },
+    /// Sends `CheckPointsSet` to dispatch batches to the checkers.
+    Check {
+        /// Points per batch, 0 for as many as each checker takes.
+        #[arg(long, default_value_t = 0)]
+        batch_size: u32,
+        /// Dispatch a single round instead of continuing until every point is sent.
+        #[arg(long)]
+        once: bool,
+    },
+}
 
-#[derive(Deserialize, Debug)]
-struct Ip {
-    jsonrpc: String,
-    id: u32,
-    result: Res,
+#[derive(Args)]
+struct RenderArgs {
+    #[command(flatten)]
+    fetch: FetchArgs,
+    #[arg(long, default_value = "output.png")]
+    output: PathBuf,
+    #[command(flatten)]
+    colors: ColorArgs,
 }
-#[derive(Deserialize, Debug)]
-struct Res {
-    payload: String,
-    value: u32,
-    code: Code,
+
+#[derive(Args)]
+struct DiffArgs {
+    #[command(flatten)]
+    fetch: FetchArgs,
+    /// Image of the comparison: mismatches in red, unchecked pixels in dark blue.
+    #[arg(long, default_value = "diff.png")]
+    output: PathBuf,
+    /// CSV file listing every mismatching pixel.
+    #[arg(long)]
+    report: Option<PathBuf>,
+    /// Also draw the local render, with the palette options.
+    #[arg(long)]
+    reference_output: Option<PathBuf>,
+    /// Mismatches printed to the terminal.
+    #[arg(long, default_value_t = 20)]
+    show: usize,
+    #[command(flatten)]
+    colors: ColorArgs,
 }
 
-#[derive(Deserialize, Debug)]
-struct Code {
-    Success: String,
+#[derive(Args)]
+struct WatchArgs {
+    #[command(flatten)]
+    fetch: FetchArgs,
+    #[arg(long, default_value = "live.png")]
+    output: PathBuf,
+    /// Seconds between two polls of the manager.
+    #[arg(long, default_value_t = 5)]
+    interval: u64,
+    /// Least seconds between two writes of the image.
+    #[arg(long, default_value_t = 15)]
+    save_interval: u64,
+    /// Color of the pixels not checked yet, as RRGGBB.
+    #[arg(long, default_value = "ff00ff", value_parser = palette::parse_color)]
+    unchecked_color: [u8; 3],
+    #[command(flatten)]
+    colors: ColorArgs,
 }
 
-#[derive(Deserialize, Debug)]
-pub struct Point {
-    pub c_re: f64,
-    pub c_im: f64,
-    pub iter: u32,
+#[derive(Args)]
+struct FetchArgs {
+    #[arg(long, default_value_t = 0)]
+    job_id: u32,
+    /// Results per `GetResults` call, capped by the manager.
+    #[arg(long, default_value_t = 20_000)]
+    page_size: u32,
+    /// Pages downloaded at the same time.
+    #[arg(long, default_value_t = 8)]
+    concurrency: usize,
+    /// Directory the completed pages are kept in between runs.
+    #[arg(long, default_value = ".rpc-cache")]
+    cache_dir: PathBuf,
+    /// Download every page again without reading or writing the cache.
+    #[arg(long)]
+    no_cache: bool,
 }
 
-fn fixed_point_to_f64(fixed_point: &FixedPoint) -> f64 {
-    fixed_point.num as f64 / 10f64.powi(fixed_point.scale as i32)
+#[derive(Args)]
+struct ColorArgs {
+    /// One of classic, grayscale, fire, ocean, ultra (cyclic), or color stops
+    /// such as `000764,edffff,ffaa00` or `cyclic:0:000764,0.5:ffaa00`.
+    #[arg(long, default_value = "classic")]
+    palette: Palette,
+    #[arg(long, value_enum, default_value_t = Coloring::Linear)]
+    coloring: Coloring,
+    #[arg(long, value_enum, default_value_t = Interpolation::Linear)]
+    interpolation: Interpolation,
+    /// Repetitions of the palette over the range of iteration counts.
+    #[arg(long, default_value_t = 1.0)]
+    cycles: f64,
+}
+
+impl ColorArgs {
+    /// `iters` are the iteration counts of the image, for histogram coloring.
+    fn colorizer(&self, max_iter: u32, iters: impl Iterator<Item = u32>) -> Colorizer {
+        Colorizer::new(
+            self.palette.clone(),
+            self.coloring,
+            self.interpolation,
+            self.cycles,
+            max_iter,
+            iters,
+        )
+    }
 }
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn Error>> {
-    let client = Client::new();
-
-    let payload = hex::encode(manager_client::manager::io::GetCheckers::encode_call());
-    let program_id = "0x8f2789dB14017AdBC83F16C0A8CaeD4aA1684272";
-    let params = json!({
-        "jsonrpc": "2.0",
-        "id": 1,
-        "method": "program_calculateReplyForHandle",
-        "params": {
-            "source": "0xf823ba3F10922DCca6970D1e012D8701f462Aa33",
-            "program_id": program_id,
-            "payload": payload,
-            "value": 0
+    let cli = Cli::parse();
+    let overrides = ConfigFile {
+        node_url: cli.overrides.node_url,
+        eth_url: cli.overrides.eth_url,
+        program_id: cli.overrides.program_id,
+        source: cli.overrides.source,
+        retries: cli.overrides.retries,
+        retry_delay_ms: cli.overrides.retry_delay_ms,
+    };
+    let config = ConfigFile::load(cli.config.as_deref())?.merge(overrides)?;
+    let client = GearExeClient::new(config)?;
+
+    match cli.command {
+        Command::Checkers { output } => checkers(&client, output).await,
+        Command::Status { job_id } => status(&client, job_id).await,
+        Command::Render(args) => render(&client, args).await,
+        Command::Watch(args) => {
+            let job = job_info(&client, args.fetch.job_id).await?;
+            let fetch = args.fetch.options(&client);
+            if let Some(cache) = &fetch.cache {
+                fetch::prepare_cache(cache, &job)?;
+            }
+            let options = WatchOptions {
+                interval: Duration::from_secs(args.interval),
+                save_interval: Duration::from_secs(args.save_interval),
+                unchecked: args.unchecked_color,
+            };
+            watch::watch(&client, &job, &fetch, &args.colors, &args.output, &options).await
         }
-    });
-
-    let url = "http://localhost:9944";
+        Command::Diff(args) => diff(&client, args).await,
+        Command::Export {
+            fetch,
+            format,
+            output,
+        } => {
+            let (params, points) = fetch_job(&client, fetch).await?;
+            export::export(format, &output, &params, &points)?;
+            println!("{} results exported to {}", points.len(), output.display());
+            Ok(())
+        }
+        Command::Generate {
+            job_id,
+            points_per_call,
+            once,
+            check,
+            batch_size,
+        } => {
+            let payload = io::GenerateAndStorePoints::encode_call(
+                job_id,
+                points_per_call,
+                !once,
+                check,
+                batch_size,
+            );
+            let tx_hash = client.send_message(payload).await?;
+            println!("GenerateAndStorePoints sent in {tx_hash}");
+            Ok(())
+        }
+        Command::Check { batch_size, once } => {
+            let payload = io::CheckPointsSet::encode_call(batch_size, !once);
+            let tx_hash = client.send_message(payload).await?;
+            println!("CheckPointsSet sent in {tx_hash}");
+            Ok(())
+        }
+    }
+}
 
-    let response = client
-        .post(url)
-        .header("Content-Type", "application/json;charset=utf-8")
-        .json(&params)
-        .send()
+async fn checkers(client: &GearExeClient, output: PathBuf) -> Result<(), Box<dyn Error>> {
+    let mut checkers = client
+        .query::<io::GetCheckers>(io::GetCheckers::encode_call())
         .await?;
-
-    let text = response.json::<Ip>().await?;
-
-    let bytes = hex::decode(&text.result.payload[2..]).unwrap();
-    let mut checkers = manager_client::manager::io::GetCheckers::decode_reply(bytes).unwrap();
     checkers.retain(|&addr| addr != ActorId::zero());
     println!("Amount of checkers {:?}", checkers.len());
 
-    let mut file = File::create("checkers.txt")?;
+    let mut file = File::create(output)?;
 
     let json_data: Vec<String> = checkers.iter().map(|id| id.to_string()).collect();
 
     let json = to_string_pretty(&json_data)?;
     writeln!(file, "{}", json)?;
+    Ok(())
+}
 
-    let total_points = 360_000;
-    let batch_size = 50_000;
-
-    let mut checked_points: Vec<PointResult> = Vec::new();
-    for start in (0..total_points).step_by(batch_size) {
-        let end = (start + batch_size).min(total_points);
-        let payload = hex::encode(manager_client::manager::io::GetResults::encode_call(
-            start as u32,
-            end as u32,
-        ));
-
-        let params = json!({
-            "jsonrpc": "2.0",
-            "id": 1,
-            "method": "program_calculateReplyForHandle",
-            "params": {
-                "source": "0xf823ba3F10922DCca6970D1e012D8701f462Aa33",
-                "program_id": program_id,
-                "payload": payload,
-                "value": 0
-            }
-        });
+async fn status(client: &GearExeClient, job_id: Option<u32>) -> Result<(), Box<dyn Error>> {
+    let jobs = match job_id {
+        Some(job_id) => {
+            let job = client
+                .query::<io::GetJob>(io::GetJob::encode_call(job_id))
+                .await?
+                .ok_or_else(|| format!("Job {job_id} not found"))?;
+            vec![job]
+        }
+        None => {
+            client
+                .query::<io::GetJobs>(io::GetJobs::encode_call())
+                .await?
+        }
+    };
+
+    if jobs.is_empty() {
+        println!("No jobs");
+    }
+    for job in &jobs {
+        print_job(job);
+    }
+    Ok(())
+}
+
+fn print_job(job: &JobInfo) {
+    let status = match job.status {
+        JobStatus::Generating => "generating",
+        JobStatus::Checking => "checking",
+        JobStatus::Completed => "completed",
+    };
+    println!(
+        "Job {}: {}x{}, max_iter {}, {status}",
+        job.id, job.params.width, job.params.height, job.params.max_iter
+    );
+    println!(
+        "  generated {}/{}, sent {}, checked {}",
+        job.points_generated, job.total_points, job.points_sent, job.points_checked
+    );
+    match (job.started_at, job.completed_at) {
+        (Some(started_at), Some(completed_at)) => println!(
+            "  started at block {started_at}, completed in {} blocks",
+            completed_at - started_at
+        ),
+        (Some(started_at), None) => println!("  started at block {started_at}"),
+        _ => {}
+    }
+}
+
+async fn job_info(client: &GearExeClient, job_id: u32) -> Result<JobInfo, Box<dyn Error>> {
+    client
+        .query::<io::GetJob>(io::GetJob::encode_call(job_id))
+        .await?
+        .ok_or_else(|| format!("Job {job_id} not found").into())
+}
+
+fn fixed_point(value: &manager_client::FixedPoint) -> FixedPoint {
+    FixedPoint {
+        num: value.num,
+        scale: value.scale,
+    }
+}
 
-        let response = client
-            .post(url)
-            .header("Content-Type", "application/json;charset=utf-8")
-            .json(&params)
-            .send()
-            .await?;
+/// Maps pixel indexes to points the same way the manager does.
+fn viewport(params: &JobParams) -> Viewport {
+    Viewport::new(
+        params.width,
+        params.height,
+        &fixed_point(&params.x_min),
+        &fixed_point(&params.x_max),
+        &fixed_point(&params.y_min),
+        &fixed_point(&params.y_max),
+    )
+}
 
-        let text = response.json::<Ip>().await?;
+/// Fails if a result does not lie where the job parameters put its pixel.
+fn check_alignment(viewport: &Viewport, results: &[PointResult]) -> Result<(), Box<dyn Error>> {
+    for point in results {
+        if point.index >= viewport.total_points() {
+            return Err(format!("Result {} lies outside the image", point.index).into());
+        }
+        let expected = viewport.coordinates(point.index);
+        let actual = (fixed_point(&point.c_re), fixed_point(&point.c_im));
+        if expected != actual {
+            return Err(format!(
+                "Result {} does not match the parameters of the job: expected ({}, {}), got ({}, {})",
+                point.index,
+                expected.0.to_decimal(),
+                expected.1.to_decimal(),
+                actual.0.to_decimal(),
+                actual.1.to_decimal(),
+            )
+            .into());
+        }
+    }
+    Ok(())
+}
 
-        let bytes = hex::decode(&text.result.payload[2..]).unwrap();
-        let points = manager_client::manager::io::GetResults::decode_reply(bytes).unwrap();
-        checked_points.extend(points);
+impl FetchArgs {
+    fn options(&self, client: &GearExeClient) -> FetchOptions {
+        FetchOptions {
+            page_size: self.page_size,
+            concurrency: self.concurrency,
+            cache: (!self.no_cache).then(|| {
+                self.cache_dir
+                    .join(client.program_id())
+                    .join(format!("job-{}", self.job_id))
+            }),
+        }
     }
+}
+
+/// Downloads the parameters and the results of a job, checking that they match.
+async fn fetch_job(
+    client: &GearExeClient,
+    args: FetchArgs,
+) -> Result<(JobParams, Vec<PointResult>), Box<dyn Error>> {
+    let job_id = args.job_id;
+    // The image is sized and mapped by the job itself
+    let job = job_info(client, job_id).await?;
+    let options = args.options(client);
+    let points = fetch::fetch_results(client, &job, &options).await?;
+    check_alignment(&viewport(&job.params), &points)?;
+    Ok((job.params, points))
+}
+
+/// Smooth iteration count of a point, for jobs that compute them.
+fn smooth(point: &PointResult) -> Option<f64> {
+    point
+        .smooth
+        .as_ref()
+        .map(|smooth| smooth.num as f64 / 10f64.powi(smooth.scale as i32))
+}
+
+async fn render(client: &GearExeClient, args: RenderArgs) -> Result<(), Box<dyn Error>> {
+    let RenderArgs {
+        fetch,
+        output,
+        colors,
+    } = args;
+    let (params, checked_points) = fetch_job(client, fetch).await?;
+    let (width, height, max_iter) = (params.width, params.height, params.max_iter);
 
     println!("Total amount of points {:?}", checked_points.len());
     if checked_points.iter().all(|point| point.checked) {
@@ -130,108 +453,124 @@ async fn main() -> Result<(), Box<dyn Error>> {
         );
     }
 
-    let points: Vec<Point> = checked_points
-        .into_iter()
-        .map(|point_result| Point {
-            c_re: fixed_point_to_f64(&point_result.c_re),
-            c_im: fixed_point_to_f64(&point_result.c_im),
-            iter: point_result.iter,
-        })
-        .collect();
-
-    //   println!("{:?}", points);
-
-    let mandelbrot_points_count = points.iter().filter(|point| point.iter == 1000).count();
+    let mandelbrot_points_count = checked_points
+        .iter()
+        .filter(|point| point.iter == max_iter)
+        .count();
 
     println!("Amount of mandelbrot points {:?}", mandelbrot_points_count);
 
-    let width = 600;
-    let height = 600;
-    let x_max: f64 = 1.0;
-    let x_min: f64 = -2.0;
-    let y_max: f64 = 1.5;
-    let y_min: f64 = -1.5;
-    let max_iter = 1000;
-
+    let colorizer = colors.colorizer(max_iter, checked_points.iter().map(|point| point.iter));
     let mut img = ImageBuffer::new(width, height);
-    let scale_x = (x_max - x_min) / width as f64;
-    let scale_y = (y_max - y_min) / height as f64;
 
-    for point in points {
-        let x = ((point.c_re - x_min) / scale_x) as u32;
-        let y = ((point.c_im - y_min) / scale_y) as u32;
+    for point in &checked_points {
+        let (x, y) = pixel_position(point.index, width);
 
         if x < width && y < height {
-            let pixel = img.get_pixel_mut(x, y);
-
-            let color = if point.iter == max_iter {
-                [0, 0, 0]
-            } else {
-                let ratio = point.iter as f64 / max_iter as f64;
-                [
-                    (255.0 * ratio) as u8,
-                    (255.0 * (1.0 - ratio)) as u8,
-                    (255.0 * (0.5 - ratio / 2.0)) as u8,
-                ]
-            };
-
-            *pixel = Rgb(color);
+            // Smooth iteration counts avoid color bands where jobs provide them
+            *img.get_pixel_mut(x, y) = Rgb(colorizer.color(point.iter, smooth(point)));
         }
     }
-    img.save("output.png").expect("Failed to save image");
-    // println!("Total amount of points {}", checked_points.len());
-    // let mut file = File::create("checked_points.txt")?;
-    // //  let json_data: Vec<(String, String, u32)> = checked_points
-    // //     .iter()
-    // //     .map(|point| ((point.c_re.clone(), point.c_im.clone(), point.iter)))
-    // //     .collect();
-
-    // let json = to_string_pretty(&checked_points)?;
-    // writeln!(file, "{}", json)?;
-
-    // let payload = hex::encode(manager_client::manager::io::GetResults::encode_call(
-    //     0, 90000,
-    // ));
-
-    // let params = json!({
-    //     "jsonrpc": "2.0",
-    //     "id": 1,
-    //     "method": "program_calculateReplyForHandle",
-    //     "params": {
-    //         "source": "0xf823ba3F10922DCca6970D1e012D8701f462Aa33",
-    //         "program_id": program_id,
-    //         "payload": payload,
-    //         "value": 0
-    //     }
-    // });
-
-    // let url = "http://35.246.144.70:9944";
-
-    // let response = client
-    //     .post(url)
-    //     .header("Content-Type", "application/json;charset=utf-8")
-    //     .json(&params)
-    //     .send()
-    //     .await?;
-
-    // let text = response.json::<Ip>().await?;
-    // // println!("Response: {:?}", text.result.payload);
-
-    // let bytes = hex::decode(&text.result.payload[2..]).unwrap();
-
-    // // let checked_points: Vec<CheckedPoint> =
-    //     manager_client::manager::io::GetResults::decode_reply(bytes).unwrap();
-    // println!("Amount of processed points {:?}", checked_points.len());
-
-    // let mut file = File::create("checked_points.txt")?;
-
-    // let json_data: Vec<(String, String, u32)> = checked_points
-    //     .iter()
-    //     .map(|point| ((point.c_re.clone(), point.c_im.clone(), point.iter)))
-    //     .collect();
-
-    // let json = to_string_pretty(&json_data)?;
-    // writeln!(file, "{}", json)?;
+    img.save(&output)?;
+    println!("Image saved to {}", output.display());
 
     Ok(())
 }
+
+fn sample_to_string(sample: &Sample) -> String {
+    match &sample.smooth {
+        Some(smooth) => format!("{} ({})", sample.iter, smooth.to_decimal()),
+        None => sample.iter.to_string(),
+    }
+}
+
+async fn diff(client: &GearExeClient, args: DiffArgs) -> Result<(), Box<dyn Error>> {
+    let DiffArgs {
+        fetch,
+        output,
+        report,
+        reference_output,
+        show,
+        colors,
+    } = args;
+    let (params, points) = fetch_job(client, fetch).await?;
+
+    println!(
+        "Rendering {}x{} pixels locally",
+        params.width, params.height
+    );
+    let samples = Reference::new(&params).render();
+    let mismatches = reference::diff(&samples, &points);
+    let checked = points.iter().filter(|point| point.checked).count();
+
+    println!(
+        "{} of {checked} checked results differ from the reference",
+        mismatches.len()
+    );
+    for mismatch in mismatches.iter().take(show) {
+        let (column, row) = pixel_position(mismatch.index, params.width);
+        println!(
+            "  pixel {} ({column}, {row}): expected {}, got {}",
+            mismatch.index,
+            sample_to_string(&mismatch.expected),
+            sample_to_string(&mismatch.actual),
+        );
+    }
+    if mismatches.len() > show {
+        println!("  and {} more", mismatches.len() - show);
+    }
+    if params.adaptive && !mismatches.is_empty() {
+        println!("The job is adaptive: pixels filled in without being computed can differ");
+    }
+
+    reference::diff_image(&params, &points, &mismatches).save(&output)?;
+    println!("Diff image saved to {}", output.display());
+
+    if let Some(report) = report {
+        let mut file = File::create(&report)?;
+        writeln!(
+            file,
+            "index,column,row,expected_iter,actual_iter,expected_smooth,actual_smooth"
+        )?;
+        for mismatch in &mismatches {
+            let (column, row) = pixel_position(mismatch.index, params.width);
+            let smooth = |sample: &Sample| {
+                sample
+                    .smooth
+                    .as_ref()
+                    .map(|smooth| smooth.to_decimal().to_string())
+                    .unwrap_or_default()
+            };
+            writeln!(
+                file,
+                "{},{column},{row},{},{},{},{}",
+                mismatch.index,
+                mismatch.expected.iter,
+                mismatch.actual.iter,
+                smooth(&mismatch.expected),
+                smooth(&mismatch.actual),
+            )?;
+        }
+        println!("Mismatches listed in {}", report.display());
+    }
+
+    if let Some(reference_output) = reference_output {
+        let colorizer = colors.colorizer(params.max_iter, samples.iter().map(|sample| sample.iter));
+        let img = ImageBuffer::from_fn(params.width, params.height, |x, y| {
+            let sample = &samples[pixel_index(x, y, params.width) as usize];
+            let smooth = sample
+                .smooth
+                .as_ref()
+                .map(|smooth| smooth.num as f64 / 10f64.powi(smooth.scale as i32));
+            Rgb(colorizer.color(sample.iter, smooth))
+        });
+        img.save(&reference_output)?;
+        println!("Reference image saved to {}", reference_output.display());
+    }
+
+    if mismatches.is_empty() {
+        Ok(())
+    } else {
+        Err(format!("{} results differ from the reference", mismatches.len()).into())
+    }
+}
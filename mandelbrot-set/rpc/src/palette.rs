@@ -0,0 +1,314 @@
+//! Coloring of the rendered iteration counts.
+//!
+//! A palette is a gradient of color stops at positions from 0 to 1. A cyclic
+//! palette blends its last stop back into the first one, so it can be repeated
+//! over the range with `cycles` without seams. The position of a pixel in the
+//! palette comes from its iteration count: either linearly (`iter / max_iter`)
+//! or through the histogram of the image, which spreads the colors evenly over
+//! the pixels whatever the zoom. Points of the set are drawn in black.
+
+use clap::ValueEnum;
+use std::str::FromStr;
+
+const INTERIOR: [u8; 3] = [0, 0, 0];
+
+#[derive(Clone, Debug)]
+pub struct Palette {
+    /// Positions from 0 to 1 in increasing order, with their colors.
+    stops: Vec<(f64, [u8; 3])>,
+    cyclic: bool,
+}
+
+impl Palette {
+    pub const PRESETS: [&'static str; 5] = ["classic", "grayscale", "fire", "ocean", "ultra"];
+
+    pub fn preset(name: &str) -> Option<Self> {
+        let (stops, cyclic): (&[(f64, u32)], bool) = match name {
+            // The original ramp of the renderer
+            "classic" => (&[(0.0, 0x00ff7f), (1.0, 0xff0000)], false),
+            "grayscale" => (&[(0.0, 0x000000), (1.0, 0xffffff)], false),
+            "fire" => (
+                &[
+                    (0.0, 0x000000),
+                    (0.3, 0x8b0000),
+                    (0.6, 0xff8c00),
+                    (0.85, 0xffd700),
+                    (1.0, 0xffffff),
+                ],
+                false,
+            ),
+            "ocean" => (
+                &[
+                    (0.0, 0x000814),
+                    (0.4, 0x003566),
+                    (0.75, 0x00b4d8),
+                    (1.0, 0xcaf0f8),
+                ],
+                false,
+            ),
+            // The gradient of Ultra Fractal, looping back to its dark blue
+            "ultra" => (
+                &[
+                    (0.0, 0x000764),
+                    (0.16, 0x206bcb),
+                    (0.42, 0xedffff),
+                    (0.6425, 0xffaa00),
+                    (0.8575, 0x000200),
+                ],
+                true,
+            ),
+            _ => return None,
+        };
+        Some(Self {
+            stops: stops
+                .iter()
+                .map(|&(position, rgb)| (position, rgb_from_u32(rgb)))
+                .collect(),
+            cyclic,
+        })
+    }
+
+    /// Color at `t`, from 0 to 1. Past 1 the palette starts over: a cyclic
+    /// one blends into its next repetition, any other restarts from its first
+    /// stop after reaching its last one.
+    pub fn color(&self, t: f64, interpolation: Interpolation) -> [u8; 3] {
+        let t = if self.cyclic {
+            t.rem_euclid(1.0)
+        } else if t > 1.0 && t.fract() == 0.0 {
+            1.0
+        } else if t > 1.0 {
+            t.fract()
+        } else {
+            t.max(0.0)
+        };
+        let first = self.stops[0];
+        let last = self.stops[self.stops.len() - 1];
+        // Stops around t; before the first stop and after the last one a
+        // cyclic palette blends between the two ends.
+        let (from, to) = match self.stops.iter().position(|&(position, _)| position > t) {
+            Some(0) if self.cyclic => ((last.0 - 1.0, last.1), first),
+            Some(0) => (first, first),
+            Some(next) => (self.stops[next - 1], self.stops[next]),
+            None if self.cyclic => (last, (first.0 + 1.0, first.1)),
+            None => (last, last),
+        };
+        match interpolation {
+            Interpolation::Nearest => from.1,
+            Interpolation::Linear if to.0 > from.0 => {
+                let ratio = (t - from.0) / (to.0 - from.0);
+                [0, 1, 2].map(|channel| {
+                    let (a, b) = (from.1[channel] as f64, to.1[channel] as f64);
+                    (a + (b - a) * ratio).round() as u8
+                })
+            }
+            Interpolation::Linear => from.1,
+        }
+    }
+}
+
+fn rgb_from_u32(rgb: u32) -> [u8; 3] {
+    [(rgb >> 16) as u8, (rgb >> 8) as u8, rgb as u8]
+}
+
+pub fn parse_color(hex: &str) -> Result<[u8; 3], String> {
+    let hex = hex.trim().trim_start_matches('#');
+    if hex.len() != 6 {
+        return Err(format!("Invalid color `{hex}`, expected RRGGBB"));
+    }
+    u32::from_str_radix(hex, 16)
+        .map(rgb_from_u32)
+        .map_err(|_| format!("Invalid color `{hex}`, expected RRGGBB"))
+}
+
+/// A preset name, or color stops separated by commas: `RRGGBB` colors spread
+/// evenly or `position:RRGGBB` pairs, prefixed with `cyclic:` for a cyclic
+/// palette.
+impl FromStr for Palette {
+    type Err = String;
+
+    fn from_str(spec: &str) -> Result<Self, Self::Err> {
+        if let Some(palette) = Self::preset(spec) {
+            return Ok(palette);
+        }
+        let (spec, cyclic) = match spec.strip_prefix("cyclic:") {
+            Some(spec) => (spec, true),
+            None => (spec, false),
+        };
+        let parts: Vec<&str> = spec.split(',').collect();
+        if parts.len() < 2 {
+            return Err(format!(
+                "Unknown palette `{spec}`: use one of {} or at least two color stops",
+                Self::PRESETS.join(", ")
+            ));
+        }
+        let spacing = 1.0 / (parts.len() - 1) as f64;
+        let mut stops = parts
+            .iter()
+            .enumerate()
+            .map(|(i, part)| match part.split_once(':') {
+                Some((position, color)) => {
+                    let position: f64 = position
+                        .trim()
+                        .parse()
+                        .map_err(|_| format!("Invalid stop position `{position}`"))?;
+                    Ok((position, parse_color(color)?))
+                }
+                None => Ok((i as f64 * spacing, parse_color(part)?)),
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+        if stops
+            .iter()
+            .any(|&(position, _)| !(0.0..=1.0).contains(&position))
+        {
+            return Err("Stop positions must be between 0 and 1".to_string());
+        }
+        stops.sort_by(|a, b| a.0.total_cmp(&b.0));
+        Ok(Self { stops, cyclic })
+    }
+}
+
+/// How an iteration count is placed in the palette.
+#[derive(Clone, Copy, Debug, ValueEnum)]
+pub enum Coloring {
+    /// `iter / max_iter`.
+    Linear,
+    /// Share of the escaped pixels that escaped sooner.
+    Histogram,
+}
+
+/// How the colors between two stops are drawn.
+#[derive(Clone, Copy, Debug, ValueEnum)]
+pub enum Interpolation {
+    /// Blend the two stops.
+    Linear,
+    /// Keep the color of the lower stop, drawing flat bands.
+    Nearest,
+}
+
+/// Colors the pixels of one image.
+pub struct Colorizer {
+    palette: Palette,
+    interpolation: Interpolation,
+    cycles: f64,
+    max_iter: u32,
+    /// Share of the escaped pixels with at most each iteration count, for
+    /// histogram coloring.
+    cdf: Option<Vec<f64>>,
+}
+
+impl Colorizer {
+    /// `iters` are the iteration counts of every pixel of the image, needed
+    /// by histogram coloring.
+    pub fn new(
+        palette: Palette,
+        coloring: Coloring,
+        interpolation: Interpolation,
+        cycles: f64,
+        max_iter: u32,
+        iters: impl Iterator<Item = u32>,
+    ) -> Self {
+        let cdf = match coloring {
+            Coloring::Linear => None,
+            Coloring::Histogram => {
+                let mut histogram = vec![0u64; max_iter as usize + 1];
+                for iter in iters.filter(|&iter| iter < max_iter) {
+                    histogram[iter as usize] += 1;
+                }
+                let total = histogram.iter().sum::<u64>().max(1) as f64;
+                let mut cumulative = 0;
+                Some(
+                    histogram
+                        .iter()
+                        .map(|count| {
+                            cumulative += count;
+                            cumulative as f64 / total
+                        })
+                        .collect(),
+                )
+            }
+        };
+        Self {
+            palette,
+            interpolation,
+            cycles,
+            max_iter,
+            cdf,
+        }
+    }
+
+    /// Color of a pixel, from its iteration count and, for smooth jobs, its
+    /// fractional count.
+    pub fn color(&self, iter: u32, smooth: Option<f64>) -> [u8; 3] {
+        if iter >= self.max_iter {
+            return INTERIOR;
+        }
+        let iter = smooth
+            .unwrap_or(iter as f64)
+            .clamp(0.0, (self.max_iter - 1) as f64);
+        let t = match &self.cdf {
+            None => iter / self.max_iter as f64,
+            Some(cdf) => {
+                let n = iter.floor() as usize;
+                let next = cdf[(n + 1).min(cdf.len() - 1)];
+                cdf[n] + (next - cdf[n]) * iter.fract()
+            }
+        };
+        self.palette.color(t * self.cycles, self.interpolation)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn presets_are_parsed_by_name() {
+        for name in Palette::PRESETS {
+            assert!(name.parse::<Palette>().is_ok(), "{name}");
+        }
+        let ultra: Palette = "ultra".parse().unwrap();
+        assert!(ultra.cyclic);
+    }
+
+    #[test]
+    fn colors_are_spread_evenly() {
+        let palette: Palette = "000000, #ff8000,ffffff".parse().unwrap();
+        assert_eq!(
+            palette.stops,
+            [
+                (0.0, [0, 0, 0]),
+                (0.5, [0xff, 0x80, 0]),
+                (1.0, [0xff, 0xff, 0xff])
+            ]
+        );
+        assert!(!palette.cyclic);
+    }
+
+    #[test]
+    fn positioned_stops_are_sorted() {
+        let palette: Palette = "cyclic:1:ffffff,0:000000,0.25:ff0000".parse().unwrap();
+        assert_eq!(
+            palette.stops,
+            [
+                (0.0, [0, 0, 0]),
+                (0.25, [0xff, 0, 0]),
+                (1.0, [0xff, 0xff, 0xff])
+            ]
+        );
+        assert!(palette.cyclic);
+    }
+
+    #[test]
+    fn invalid_palettes_are_rejected() {
+        for spec in [
+            "rainbow",
+            "ff0000",
+            "ff0000,00ff",
+            "ff0000,gg0000",
+            "x:ff0000,1:00ff00",
+            "0:ff0000,1.5:00ff00",
+        ] {
+            assert!(spec.parse::<Palette>().is_err(), "{spec}");
+        }
+    }
+}
@@ -0,0 +1,272 @@
+//! Local render of a job with the arithmetic of the checker, to audit the
+//! results of the distributed computation.
+//!
+//! Every pixel is mapped to the coordinates the manager sends for it and run
+//! through the same `mandelbrot_common` kernel as the checker program, or
+//! through its perturbation for deep-zoom jobs, so an honest checker returns
+//! exactly the same iteration counts and smooth counts.
+
+use crate::{smooth, viewport};
+use image::{ImageBuffer, Rgb, RgbImage};
+use manager_client::{JobParams, PointResult};
+use mandelbrot_common::{
+    grid::pixel_position,
+    kernel,
+    perturbation::{self, Perturbation},
+    FixedPoint, Fractal, Viewport,
+};
+use sails_rs::{Decode, Encode};
+use std::thread;
+
+const MATCH_SHADE: f64 = 160.0;
+const MISMATCH: [u8; 3] = [255, 0, 0];
+const UNCHECKED: [u8; 3] = [0, 0, 96];
+
+/// Converts a type of the manager client into the `mandelbrot_common` type
+/// it was generated from.
+fn convert<T: Decode>(value: &impl Encode) -> T {
+    T::decode(&mut value.encode().as_slice()).expect("Client and common types encode alike")
+}
+
+/// Iteration count of a pixel, with its smooth count for smooth jobs.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Sample {
+    pub iter: u32,
+    pub smooth: Option<FixedPoint>,
+}
+
+pub struct Reference {
+    viewport: Viewport,
+    fractal: Fractal,
+    max_iter: u32,
+    smooth: bool,
+    perturbation: Option<Perturbation>,
+}
+
+impl Reference {
+    pub fn new(params: &JobParams) -> Self {
+        let perturbation = params.deep_zoom.as_ref().map(|deep_zoom| {
+            Perturbation::new(&perturbation::reference_orbit(
+                &convert(&deep_zoom.center_re),
+                &convert(&deep_zoom.center_im),
+                deep_zoom.offset_scale,
+                params.max_iter,
+            ))
+        });
+        Self {
+            viewport: viewport(params),
+            fractal: convert(&params.fractal),
+            max_iter: params.max_iter,
+            smooth: params.smooth,
+            perturbation,
+        }
+    }
+
+    /// What the checker computes for the pixel.
+    pub fn sample(&self, index: u32) -> Sample {
+        let (c_re, c_im) = self.viewport.coordinates(index);
+        let (c_re, c_im) = (&c_re, &c_im);
+        let max_iter = self.max_iter;
+        if self.smooth {
+            let (iter, smooth) = match &self.perturbation {
+                Some(perturbation) => perturbation.smooth_escape_time(c_re, c_im, max_iter),
+                None => kernel::smooth_escape_time(&self.fractal, c_re, c_im, max_iter),
+            };
+            Sample {
+                iter,
+                smooth: Some(smooth),
+            }
+        } else {
+            let iter = match &self.perturbation {
+                Some(perturbation) => perturbation.escape_time(c_re, c_im, max_iter),
+                None => kernel::escape_time(&self.fractal, c_re, c_im, max_iter),
+            };
+            Sample { iter, smooth: None }
+        }
+    }
+
+    /// Samples every pixel of the image, spread over the available cores.
+    pub fn render(&self) -> Vec<Sample> {
+        let total = self.viewport.total_points();
+        let threads = thread::available_parallelism().map_or(1, |threads| threads.get()) as u32;
+        let chunk = total.div_ceil(threads).max(1);
+        thread::scope(|scope| {
+            let handles: Vec<_> = (0..total)
+                .step_by(chunk as usize)
+                .map(|start| {
+                    scope.spawn(move || {
+                        (start..(start + chunk).min(total))
+                            .map(|index| self.sample(index))
+                            .collect::<Vec<_>>()
+                    })
+                })
+                .collect();
+            handles
+                .into_iter()
+                .flat_map(|handle| handle.join().expect("Reference thread panicked"))
+                .collect()
+        })
+    }
+}
+
+/// A checked result that differs from the reference.
+pub struct Mismatch {
+    pub index: u32,
+    pub expected: Sample,
+    pub actual: Sample,
+}
+
+/// Compares the checked results with the reference samples of their pixels.
+pub fn diff(reference: &[Sample], points: &[PointResult]) -> Vec<Mismatch> {
+    points
+        .iter()
+        .filter(|point| point.checked)
+        .filter_map(|point| {
+            let expected = reference.get(point.index as usize)?;
+            let actual = Sample {
+                iter: point.iter,
+                smooth: point.smooth.as_ref().map(convert),
+            };
+            (actual != *expected).then(|| Mismatch {
+                index: point.index,
+                expected: expected.clone(),
+                actual,
+            })
+        })
+        .collect()
+}
+
+/// Draws the matching pixels in gray, brighter the longer they take to
+/// escape, the mismatching ones in red and the unchecked ones in dark blue.
+pub fn diff_image(params: &JobParams, points: &[PointResult], mismatches: &[Mismatch]) -> RgbImage {
+    let mut image = ImageBuffer::from_pixel(params.width, params.height, Rgb(UNCHECKED));
+    for point in points.iter().filter(|point| point.checked) {
+        let (x, y) = pixel_position(point.index, params.width);
+        if let Some(pixel) = image.get_pixel_mut_checked(x, y) {
+            let shade = (MATCH_SHADE * smooth(point).unwrap_or(point.iter as f64)
+                / params.max_iter as f64) as u8;
+            *pixel = Rgb([shade; 3]);
+        }
+    }
+    for mismatch in mismatches {
+        let (x, y) = pixel_position(mismatch.index, params.width);
+        if let Some(pixel) = image.get_pixel_mut_checked(x, y) {
+            *pixel = Rgb(MISMATCH);
+        }
+    }
+    image
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MAX_ITER: u32 = 50;
+
+    // Pixels at -2, -1 and 0 on the real axis, 1.5 and 0 on the imaginary
+    fn params(smooth: bool) -> JobParams {
+        JobParams {
+            fractal: manager_client::Fractal::Mandelbrot,
+            width: 3,
+            height: 2,
+            x_min: manager_client::FixedPoint { num: -2, scale: 0 },
+            x_max: manager_client::FixedPoint { num: 1, scale: 0 },
+            y_min: manager_client::FixedPoint { num: -15, scale: 1 },
+            y_max: manager_client::FixedPoint { num: 15, scale: 1 },
+            max_iter: MAX_ITER,
+            smooth,
+            deep_zoom: None,
+            adaptive: false,
+        }
+    }
+
+    fn point(index: u32, sample: &Sample, checked: bool) -> PointResult {
+        PointResult {
+            index,
+            c_re: manager_client::FixedPoint { num: 0, scale: 0 },
+            c_im: manager_client::FixedPoint { num: 0, scale: 0 },
+            iter: sample.iter,
+            smooth: sample.smooth.as_ref().map(convert),
+            checked,
+        }
+    }
+
+    #[test]
+    fn samples_match_the_checker_kernel() {
+        for smooth in [false, true] {
+            let params = params(smooth);
+            let reference = Reference::new(&params);
+            let viewport = viewport(&params);
+            for index in 0..viewport.total_points() {
+                let (c_re, c_im) = viewport.coordinates(index);
+                let expected = if smooth {
+                    let (iter, smooth) =
+                        kernel::smooth_escape_time(&Fractal::Mandelbrot, &c_re, &c_im, MAX_ITER);
+                    Sample {
+                        iter,
+                        smooth: Some(smooth),
+                    }
+                } else {
+                    Sample {
+                        iter: kernel::escape_time(&Fractal::Mandelbrot, &c_re, &c_im, MAX_ITER),
+                        smooth: None,
+                    }
+                };
+                assert_eq!(reference.sample(index), expected);
+            }
+        }
+    }
+
+    #[test]
+    fn samples_follow_the_pixel_index() {
+        let reference = Reference::new(&params(false));
+        // -2 + 1.5i escapes at once, the origin never does
+        assert!(reference.sample(0).iter < MAX_ITER);
+        assert_eq!(reference.sample(5).iter, MAX_ITER);
+        assert_eq!(
+            reference.render(),
+            (0..6)
+                .map(|index| reference.sample(index))
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn diff_reports_checked_mismatches() {
+        let samples = Reference::new(&params(false)).render();
+        let wrong = Sample {
+            iter: samples[1].iter + 1,
+            smooth: None,
+        };
+        let points = [
+            point(0, &samples[0], true),
+            point(1, &wrong, true),
+            // Unchecked results are not compared
+            point(2, &wrong, false),
+            // Neither are results outside the image
+            point(6, &wrong, true),
+        ];
+
+        let mismatches = diff(&samples, &points);
+        assert_eq!(mismatches.len(), 1);
+        assert_eq!(mismatches[0].index, 1);
+        assert_eq!(mismatches[0].expected, samples[1]);
+        assert_eq!(mismatches[0].actual, wrong);
+    }
+
+    #[test]
+    fn diff_compares_smooth_counts() {
+        let samples = Reference::new(&params(true)).render();
+        let mut wrong = samples[0].clone();
+        wrong.smooth = wrong.smooth.map(|smooth| FixedPoint {
+            num: smooth.num + 1,
+            scale: smooth.scale,
+        });
+        let points = [point(0, &wrong, true), point(1, &samples[1], true)];
+
+        let mismatches = diff(&samples, &points);
+        assert_eq!(mismatches.len(), 1);
+        assert_eq!(mismatches[0].index, 0);
+        assert_eq!(mismatches[0].actual, wrong);
+    }
+}
@@ -0,0 +1,279 @@
+//! Live preview of a job while the checkers compute it.
+//!
+//! The image is cut into the pages of `GetResults`, bands of consecutive
+//! pixels. Every poll downloads the pages that still have pixels to check and
+//! repaints the pixels whose result changed since the previous one; a page
+//! whose pixels are all checked is not asked for again while
+//! `JobInfo::results_version` stays the same. When it changes, the job was
+//! restarted or a result was corrected: every page is downloaded again, and
+//! the pixels that lost their result go back to the `unchecked` color. The
+//! PNG is written at most every `save_interval` while pixels change, and once
+//! more when the job is complete.
+
+use crate::{
+    check_alignment,
+    client::GearExeClient,
+    fetch::{self, FetchOptions},
+    palette::Coloring,
+    smooth, viewport, ColorArgs,
+};
+use image::{ImageBuffer, ImageFormat, Rgb, RgbImage};
+use indicatif::ProgressBar;
+use manager_client::{manager::io, JobInfo, PointResult};
+use mandelbrot_common::grid::pixel_position;
+use std::{
+    collections::HashMap,
+    error::Error,
+    fs,
+    path::Path,
+    time::{Duration, Instant},
+};
+
+pub struct WatchOptions {
+    /// Time between two polls of the manager.
+    pub interval: Duration,
+    /// Least time between two writes of the image.
+    pub save_interval: Duration,
+    pub unchecked: [u8; 3],
+}
+
+/// Iteration count and smooth count of every checked pixel.
+type Samples = Vec<Option<(u32, Option<f64>)>>;
+
+pub async fn watch(
+    client: &GearExeClient,
+    job: &JobInfo,
+    fetch: &FetchOptions,
+    colors: &ColorArgs,
+    output: &Path,
+    options: &WatchOptions,
+) -> Result<(), Box<dyn Error>> {
+    let (job_id, params) = (job.id, &job.params);
+    let total = params.width * params.height;
+    let viewport = viewport(params);
+    let all_pages = fetch::pages(total, fetch.page_size);
+    let mut pending = all_pages.clone();
+    let mut results_version = job.results_version;
+    let mut samples: Samples = vec![None; total as usize];
+    let mut image = ImageBuffer::from_pixel(params.width, params.height, Rgb(options.unchecked));
+    // Histogram colors depend on every pixel and are redrawn before each save
+    let mut colorizer = colors.colorizer(params.max_iter, std::iter::empty());
+    let histogram = matches!(colors.coloring, Coloring::Histogram);
+    let mut unsaved = false;
+    let mut last_save: Option<Instant> = None;
+
+    loop {
+        let job = client
+            .query::<io::GetJob>(io::GetJob::encode_call(job_id))
+            .await?
+            .ok_or_else(|| format!("Job {job_id} was removed"))?;
+        let generated = job.points_generated;
+        if job.results_version != results_version {
+            println!("Job {job_id} was restarted or corrected, downloading it again");
+            results_version = job.results_version;
+            pending = all_pages.clone();
+            if let Some(cache) = &fetch.cache {
+                fetch::prepare_cache(cache, &job)?;
+            }
+            let forgotten = forget(&mut samples, generated);
+            unsaved |= !forgotten.is_empty();
+            paint(
+                &mut image,
+                &samples,
+                params.width,
+                &forgotten,
+                options.unchecked,
+                |iter, smooth| colorizer.color(iter, smooth),
+            );
+        }
+        let due: Vec<(u32, u32)> = pending
+            .iter()
+            .copied()
+            .filter(|&(start, _)| start < generated)
+            .collect();
+        let ends: HashMap<u32, u32> = due.iter().copied().collect();
+        let pages = fetch::fetch_pages(client, job_id, &due, fetch, &ProgressBar::hidden()).await?;
+
+        for (start, results) in pages {
+            check_alignment(&viewport, &results)?;
+            let complete = results.len() as u32 == ends[&start] - start
+                && results.iter().all(|point| point.checked);
+            if complete {
+                pending.retain(|&(page_start, _)| page_start != start);
+            }
+            let changed = update(&mut samples, &results);
+            unsaved |= !changed.is_empty();
+            paint(
+                &mut image,
+                &samples,
+                params.width,
+                &changed,
+                options.unchecked,
+                |iter, smooth| colorizer.color(iter, smooth),
+            );
+        }
+
+        let done = pending.is_empty();
+        let checked = samples.iter().flatten().count();
+        println!(
+            "{checked}/{total} pixels checked ({:.1}%)",
+            checked as f64 * 100.0 / total as f64
+        );
+        let save_due = match last_save {
+            Some(saved) => saved.elapsed() >= options.save_interval,
+            None => true,
+        };
+        if unsaved && (save_due || done) {
+            if histogram {
+                colorizer = colors.colorizer(
+                    params.max_iter,
+                    samples.iter().flatten().map(|&(iter, _)| iter),
+                );
+                repaint(&mut image, &samples, params.width, |iter, smooth| {
+                    colorizer.color(iter, smooth)
+                });
+            }
+            save(&image, output)?;
+            unsaved = false;
+            last_save = Some(Instant::now());
+        }
+        if done {
+            println!(
+                "Job {job_id} is complete, image saved to {}",
+                output.display()
+            );
+            return Ok(());
+        }
+        tokio::time::sleep(options.interval).await;
+    }
+}
+
+/// Records the results of a page and returns the pixels whose sample changed:
+/// newly checked, corrected, or unchecked again after a restart.
+fn update(samples: &mut Samples, results: &[PointResult]) -> Vec<u32> {
+    results
+        .iter()
+        .filter_map(|point| {
+            let sample = point.checked.then(|| (point.iter, smooth(point)));
+            let slot = &mut samples[point.index as usize];
+            (*slot != sample).then(|| {
+                *slot = sample;
+                point.index
+            })
+        })
+        .collect()
+}
+
+/// Drops the samples of the pixels from `generated` on, which a restart threw
+/// away, and returns the pixels that had one.
+fn forget(samples: &mut Samples, generated: u32) -> Vec<u32> {
+    let start = (generated as usize).min(samples.len());
+    samples[start..]
+        .iter_mut()
+        .enumerate()
+        .filter_map(|(offset, sample)| sample.take().map(|_| (start + offset) as u32))
+        .collect()
+}
+
+/// Paints the given pixels with the color of their sample, or `unchecked`
+/// when they have none.
+fn paint(
+    image: &mut RgbImage,
+    samples: &Samples,
+    width: u32,
+    indexes: &[u32],
+    unchecked: [u8; 3],
+    color: impl Fn(u32, Option<f64>) -> [u8; 3],
+) {
+    for &index in indexes {
+        let (x, y) = pixel_position(index, width);
+        let pixel = match samples[index as usize] {
+            Some((iter, smooth)) => color(iter, smooth),
+            None => unchecked,
+        };
+        image.put_pixel(x, y, Rgb(pixel));
+    }
+}
+
+fn repaint(
+    image: &mut RgbImage,
+    samples: &Samples,
+    width: u32,
+    color: impl Fn(u32, Option<f64>) -> [u8; 3],
+) {
+    for (index, sample) in samples.iter().enumerate() {
+        if let Some((iter, smooth)) = sample {
+            let (x, y) = pixel_position(index as u32, width);
+            image.put_pixel(x, y, Rgb(color(*iter, *smooth)));
+        }
+    }
+}
+
+/// Writes through a temporary file, so viewers never load a partial image.
+fn save(image: &RgbImage, output: &Path) -> Result<(), Box<dyn Error>> {
+    let tmp = output.with_extension("tmp.png");
+    image.save_with_format(&tmp, ImageFormat::Png)?;
+    fs::rename(tmp, output)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use manager_client::FixedPoint;
+
+    const UNCHECKED: [u8; 3] = [255, 0, 255];
+
+    fn point(index: u32, iter: u32, checked: bool) -> PointResult {
+        PointResult {
+            index,
+            c_re: FixedPoint { num: 0, scale: 0 },
+            c_im: FixedPoint { num: 0, scale: 0 },
+            iter,
+            smooth: None,
+            checked,
+        }
+    }
+
+    fn gray(iter: u32, _smooth: Option<f64>) -> [u8; 3] {
+        [iter as u8; 3]
+    }
+
+    #[test]
+    fn update_reports_newly_checked_pixels_once() {
+        let mut samples: Samples = vec![None; 4];
+        let page = [point(0, 5, true), point(1, 7, false), point(2, 9, true)];
+        assert_eq!(update(&mut samples, &page), [0, 2]);
+        assert_eq!(samples, [Some((5, None)), None, Some((9, None)), None]);
+        assert!(update(&mut samples, &page).is_empty());
+    }
+
+    #[test]
+    fn update_reports_corrected_and_reset_pixels() {
+        let mut samples: Samples = vec![Some((5, None)), Some((7, None)), Some((9, None))];
+        // A lost dispute corrected pixel 1 and a restart unchecked pixel 2
+        let page = [point(0, 5, true), point(1, 8, true), point(2, 0, false)];
+        assert_eq!(update(&mut samples, &page), [1, 2]);
+        assert_eq!(samples, [Some((5, None)), Some((8, None)), None]);
+    }
+
+    #[test]
+    fn forget_drops_the_pixels_not_generated_any_more() {
+        let mut samples: Samples = vec![Some((1, None)), Some((2, None)), None, Some((4, None))];
+        assert_eq!(forget(&mut samples, 1), [1, 3]);
+        assert_eq!(samples, [Some((1, None)), None, None, None]);
+        assert!(forget(&mut samples, 10).is_empty());
+    }
+
+    #[test]
+    fn paint_uses_the_unchecked_color_without_a_sample() {
+        let mut image = ImageBuffer::from_pixel(2, 2, Rgb([0, 0, 0]));
+        let samples: Samples = vec![Some((10, None)), None, Some((30, None)), None];
+        paint(&mut image, &samples, 2, &[0, 1, 2], UNCHECKED, gray);
+        assert_eq!(image.get_pixel(0, 0), &Rgb([10; 3]));
+        assert_eq!(image.get_pixel(1, 0), &Rgb(UNCHECKED));
+        assert_eq!(image.get_pixel(0, 1), &Rgb([30; 3]));
+        // Pixels not listed are left alone
+        assert_eq!(image.get_pixel(1, 1), &Rgb([0, 0, 0]));
+    }
+}